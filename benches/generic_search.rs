@@ -0,0 +1,67 @@
+//! The search algorithms of Chapter 2 on a 1000 by 1000 maze, from one corner to the other.
+use classic_computer_science_problems::{
+    generic_search::{astar, bfs, dfs},
+    maze::{Maze, MazeLocation},
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use ordered_float::OrderedFloat;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn maze() -> Maze {
+    let size = 1000;
+    let start = MazeLocation { row: 0, column: 0 };
+    let goal = MazeLocation {
+        row: size as i32 - 1,
+        column: size as i32 - 1,
+    };
+
+    // Seeded, so every run searches the same maze, which has a path from the start to the goal.
+    Maze::new_with_setup_and_rng(size, size, start, goal, 0.2, &mut StdRng::seed_from_u64(1))
+}
+
+fn search(c: &mut Criterion) {
+    let maze = maze();
+    let mut group = c.benchmark_group("1000x1000 maze");
+
+    group.sample_size(10);
+
+    group.bench_function("dfs", |b| {
+        b.iter(|| {
+            dfs(
+                maze.start,
+                |loc| maze.test_goal(loc),
+                |loc| maze.get_successors(loc),
+            )
+            .unwrap()
+            .to_path()
+        })
+    });
+    group.bench_function("bfs", |b| {
+        b.iter(|| {
+            bfs(
+                maze.start,
+                |loc| maze.test_goal(loc),
+                |loc| maze.get_successors(loc),
+            )
+            .unwrap()
+            .to_path()
+        })
+    });
+    group.bench_function("astar", |b| {
+        b.iter(|| {
+            astar(
+                maze.start,
+                |loc| maze.test_goal(loc),
+                |loc| maze.get_successors(loc),
+                |loc| OrderedFloat(maze.distance_to_goal(loc).into()),
+            )
+            .unwrap()
+            .to_path()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
@@ -19,7 +19,7 @@
 /// Java implementation: https://github.com/davecom/ClassicComputerScienceProblemsInJava/blob/master/CCSPiJ/src/chapter2/Maze.java
 ///
 use classic_computer_science_problems::{
-    generic_search::{astar, bfs, dfs},
+    generic_search::{astar, bfs, dfs, dijkstra, dijkstra_all},
     maze::{Maze, MazeLocation},
 };
 use ordered_float::OrderedFloat;
@@ -74,6 +74,44 @@ fn main() {
 
     maze.clear_path();
 
+    // Every move costs the same here, so Dijkstra's algorithm finds a path as short as A*'s, just with more searching.
+    let successors = |loc| {
+        Maze::get_successors(&maze, loc)
+            .into_iter()
+            .map(|succ| (succ, OrderedFloat(1.0)))
+            .collect()
+    };
+
+    let solution = dijkstra(maze.start, |loc| Maze::test_goal(&maze, loc), successors);
+    let shortest_paths = dijkstra_all(maze.start, successors);
+
+    match solution {
+        Some(solution) => {
+            println!("Found solution with Dijkstra's algorithm!");
+
+            let path = solution.to_path();
+
+            maze.mark_path(Vec::from(path.clone()));
+
+            println!("Solution path:");
+            println!("{}", maze);
+            println!("Took {} steps", path.len());
+        }
+        None => {
+            println!("No solution found :-(");
+            println!("Perhaps the start or the goal are blocked off?");
+        }
+    }
+
+    println!(
+        "{} of the maze's cells can be reached from the start",
+        shortest_paths.distances.len()
+    );
+
+    println!("{:=^width$}", "=", width = rows * 3);
+
+    maze.clear_path();
+
     let solution = bfs(
         maze.start,
         |loc| Maze::test_goal(&maze, loc),
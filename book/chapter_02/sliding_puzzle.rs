@@ -0,0 +1,151 @@
+/// # Sliding Puzzle
+///
+/// The 8-puzzle: eight numbered tiles on a 3 by 3 board, with one gap that the tiles next to it can slide into.
+/// The goal is to get the tiles in order, with the gap in the bottom right corner.
+///
+/// There are 181,440 boards that can be reached from any one board, which `bfs` or `astar` would end up keeping
+/// in memory on the harder puzzles. Iterative deepening only keeps the moves it is trying, at the cost of trying
+/// some of them over and over again: `iddfs` is fine for a short puzzle, but `ida_star` needs a heuristic for the longer ones.
+///
+/// Not in the book.
+///
+use std::fmt;
+
+use classic_computer_science_problems::generic_search::{ida_star, iddfs, IterativeDeepening};
+use ordered_float::OrderedFloat;
+
+const SIZE: usize = 3;
+
+/// The tile on each square, row by row, with 0 for the gap.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Board([u8; SIZE * SIZE]);
+
+impl Board {
+    const SOLVED: Board = Board([1, 2, 3, 4, 5, 6, 7, 8, 0]);
+
+    fn test_goal(&self) -> bool {
+        *self == Self::SOLVED
+    }
+
+    /// Every board one slide away from this one.
+    fn get_successors(&self) -> Vec<Board> {
+        let gap = self.0.iter().position(|&tile| tile == 0).unwrap();
+        let (row, column) = (gap / SIZE, gap % SIZE);
+
+        let mut neighbours = Vec::new();
+
+        if row > 0 {
+            neighbours.push(gap - SIZE);
+        }
+        if row < SIZE - 1 {
+            neighbours.push(gap + SIZE);
+        }
+        if column > 0 {
+            neighbours.push(gap - 1);
+        }
+        if column < SIZE - 1 {
+            neighbours.push(gap + 1);
+        }
+
+        neighbours
+            .into_iter()
+            .map(|neighbour| {
+                let mut tiles = self.0;
+
+                tiles.swap(gap, neighbour);
+
+                Board(tiles)
+            })
+            .collect()
+    }
+
+    /// How far each tile is from where it belongs, in rows and columns.
+    /// Each slide moves one tile by one square, so this never overestimates the number of slides left.
+    fn manhattan_distance_to_goal(&self) -> u32 {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile != 0)
+            .map(|(square, &tile)| {
+                let goal = tile as usize - 1;
+
+                (square / SIZE).abs_diff(goal / SIZE) as u32
+                    + (square % SIZE).abs_diff(goal % SIZE) as u32
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.0.chunks(SIZE) {
+            for &tile in row {
+                if tile == 0 {
+                    write!(f, "[ ]")?;
+                } else {
+                    write!(f, "[{tile}]")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn report(name: &str, found: IterativeDeepening<Board>) {
+    match found.solution {
+        Some(solution) => {
+            println!(
+                "Found a solution with {name} in {} iterations!",
+                found.iterations
+            );
+            println!("Took {} slides:", solution.cost());
+
+            for board in solution.to_path() {
+                println!("{board}");
+            }
+        }
+        None if found.exhausted => {
+            println!("{name} found no solution: this puzzle cannot be solved.")
+        }
+        None => println!(
+            "{name} found no solution within the limit, after {} iterations.",
+            found.iterations
+        ),
+    }
+}
+
+fn main() {
+    let short = Board([4, 1, 3, 7, 2, 6, 0, 5, 8]);
+
+    println!("{short}");
+
+    report(
+        "IDDFS",
+        iddfs(
+            short,
+            |board| board.test_goal(),
+            |board| board.get_successors(),
+            20,
+        ),
+    );
+
+    println!("{:=^width$}", "=", width = 30);
+
+    let long = Board([7, 2, 4, 5, 0, 6, 8, 3, 1]);
+
+    println!("{long}");
+
+    report(
+        "IDA*",
+        ida_star(
+            long,
+            |board| board.test_goal(),
+            |board| board.get_successors(),
+            |board| OrderedFloat(board.manhattan_distance_to_goal().into()),
+            OrderedFloat(31.0),
+        ),
+    );
+}
@@ -13,7 +13,7 @@
 /// Java implementation: https://github.com/davecom/ClassicComputerScienceProblemsInJava/blob/master/CCSPiJ/src/chapter3/MapColoringConstraint.java
 use std::collections::HashMap;
 
-use classic_computer_science_problems::csp::{Constraint, CSP};
+use classic_computer_science_problems::csp::{Constraint, Solution, Symmetry, CSP};
 
 use console::{style, StyledObject};
 
@@ -113,7 +113,8 @@ fn main() {
     // println!("Constraints:");
     // println!("{:?}", csp.constraints);
 
-    let solution = csp.backtracking_search();
+    // Sorted by region, so the output is the same on every run.
+    let solution = csp.backtracking_search().map(Solution::from);
 
     match solution {
         Some(solution) => {
@@ -125,4 +126,31 @@ fn main() {
         }
         None => println!("No solution found :-("),
     }
+
+    // Swapping the colours around in a solution gives another one, so most colourings come in sets of six.
+    // Declaring the colours interchangeable keeps one of each set.
+    println!("{} colourings in all", csp.count_solutions());
+
+    csp.add_symmetry(Symmetry::InterchangeableValues(vec![
+        "red", "green", "blue",
+    ]))
+    .expect("Valid symmetry");
+
+    println!(
+        "{} once the colours are interchangeable",
+        csp.count_solutions()
+    );
+
+    // With only two colours, there is no solution at all. Ask which borders are to blame.
+    for domain in csp.domains.values_mut() {
+        domain.retain(|&colour| colour != "blue");
+    }
+
+    if let Some(conflict) = csp.minimal_unsatisfiable_subset() {
+        println!("With only red and green, these borders cannot all be respected:");
+
+        for constraint in conflict {
+            println!("{} - {}", constraint.place_1, constraint.place_2);
+        }
+    }
 }
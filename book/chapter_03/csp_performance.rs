@@ -0,0 +1,240 @@
+/// # CSP performance
+///
+/// Times the backtracking search in `csp` against the recursive search from the book, on the eight queens and SEND+MORE=MONEY puzzles.
+///
+/// The book's version clones the whole assignment for every value it tries and again when it recurses.
+/// `CSP::solutions` instead keeps a single assignment and a trail of pruned values that it unwinds on backtracking.
+/// Integer domains can also be kept as bit sets or interval sets rather than a flag per value,
+/// and values can be tried least constraining first rather than in the order of their domain.
+/// Finally, every solution is counted once on a single thread and once on all of them.
+///
+/// Run this with `cargo run --release --bin csp_performance`; debug builds are far too slow to say anything useful.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use classic_computer_science_problems::csp::{
+    Constraint, DomainRepresentation, Inference, ValueOrdering, VariableOrdering, CSP,
+};
+
+#[derive(Clone, Debug)]
+struct QueensConstraint {
+    columns: Vec<i32>,
+}
+
+impl Constraint<i32, i32> for QueensConstraint {
+    fn variables(&self) -> Vec<i32> {
+        self.columns.clone()
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<i32, i32>) -> bool {
+        for (&q1c, &q1r) in assignment {
+            for q2c in (q1c + 1)..=(self.columns.len() as i32) {
+                if let Some(&q2r) = assignment.get(&q2c) {
+                    if q1r == q2r || (q1r - q2r).abs() == (q1c - q2c).abs() {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SendMoreMoneyConstraint {
+    letters: Vec<char>,
+}
+
+impl Constraint<char, i32> for SendMoreMoneyConstraint {
+    fn variables(&self) -> Vec<char> {
+        self.letters.clone()
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<char, i32>) -> bool {
+        if HashSet::<&i32>::from_iter(assignment.values()).len() < assignment.len() {
+            return false;
+        }
+
+        if assignment.len() == self.letters.len() {
+            let value = |letter| assignment[&letter];
+
+            let send = value('S') * 1000 + value('E') * 100 + value('N') * 10 + value('D');
+            let more = value('M') * 1000 + value('O') * 100 + value('R') * 10 + value('E');
+            let money = value('M') * 10000
+                + value('O') * 1000
+                + value('N') * 100
+                + value('E') * 10
+                + value('Y');
+
+            return send + more == money;
+        }
+
+        true
+    }
+}
+
+fn eight_queens() -> CSP<i32, i32, QueensConstraint> {
+    let columns: Vec<i32> = (1..=8).collect();
+    let rows = columns
+        .iter()
+        .map(|&column| (column, columns.clone()))
+        .collect();
+
+    let mut csp = CSP::new(columns.clone(), rows).unwrap();
+
+    csp.add_constraint(QueensConstraint { columns }).unwrap();
+
+    csp
+}
+
+fn send_more_money() -> CSP<char, i32, SendMoreMoneyConstraint> {
+    let letters = vec!['S', 'E', 'N', 'D', 'M', 'O', 'R', 'Y'];
+    let mut digits: HashMap<char, Vec<i32>> = letters
+        .iter()
+        .map(|&letter| (letter, (0..=9).collect()))
+        .collect();
+
+    digits.insert('M', vec![1]);
+
+    let mut csp = CSP::new(letters.clone(), digits).unwrap();
+
+    csp.add_constraint(SendMoreMoneyConstraint { letters })
+        .unwrap();
+
+    csp
+}
+
+/// The recursive search from the book, kept here as the baseline.
+fn book_backtracking_search<V, D, C>(
+    csp: &CSP<V, D, C>,
+    assignment: HashMap<V, D>,
+) -> Option<HashMap<V, D>>
+where
+    V: Clone + Hash + Eq,
+    D: Clone,
+    C: Constraint<V, D>,
+{
+    if assignment.len() == csp.variables.len() {
+        return Some(assignment);
+    }
+
+    let unassigned = csp.variables.iter().find(|v| !assignment.contains_key(v))?;
+
+    for value in csp.domains.get(unassigned)? {
+        let mut local_assignment = assignment.clone();
+
+        local_assignment.insert(unassigned.clone(), value.clone());
+
+        let consistent = csp.constraints.get(unassigned).is_none_or(|constraints| {
+            constraints
+                .iter()
+                .all(|constraint| constraint.is_satisfied(&local_assignment))
+        });
+
+        if consistent {
+            if let Some(result) = book_backtracking_search(csp, local_assignment.clone()) {
+                return Some(result);
+            }
+        }
+    }
+
+    None
+}
+
+/// Run `search` repeatedly for about a second and return the average time per run.
+fn time<T>(mut search: impl FnMut() -> T) -> Duration {
+    let started = Instant::now();
+    let mut runs = 0;
+
+    while runs == 0 || started.elapsed() < Duration::from_secs(1) {
+        std::hint::black_box(search());
+        runs += 1;
+    }
+
+    started.elapsed() / runs
+}
+
+fn compare<V, D, C>(name: &str, mut csp: CSP<V, D, C>)
+where
+    V: Clone + std::fmt::Debug + Hash + Ord + Send + Sync,
+    D: Copy + std::fmt::Debug + Into<i64> + Send + Sync,
+    C: Clone + Constraint<V, D> + Send + Sync,
+{
+    println!("{name}:");
+    println!(
+        "  {:<56} {:>12?}",
+        "book (recursive, cloning)",
+        time(|| book_backtracking_search(&csp, HashMap::new()))
+    );
+
+    for (variable_ordering, inference) in [
+        (VariableOrdering::FirstUnassigned, Inference::None),
+        (
+            VariableOrdering::MinimumRemainingValues,
+            Inference::ForwardChecking,
+        ),
+        (
+            VariableOrdering::MinimumRemainingValues,
+            Inference::MaintainArcConsistency,
+        ),
+    ] {
+        csp.variable_ordering = variable_ordering;
+        csp.inference = inference;
+
+        println!(
+            "  {:<56} {:>12?}",
+            format!("{variable_ordering:?}, {inference:?}"),
+            time(|| csp.backtracking_search())
+        );
+    }
+
+    csp.variable_ordering = VariableOrdering::MinimumRemainingValues;
+    csp.inference = Inference::ForwardChecking;
+
+    for domain_representation in [
+        DomainRepresentation::bit_set(),
+        DomainRepresentation::interval(),
+    ] {
+        csp.domain_representation = domain_representation;
+
+        println!(
+            "  {:<56} {:>12?}",
+            format!("MinimumRemainingValues, ForwardChecking, {domain_representation:?}"),
+            time(|| csp.backtracking_search())
+        );
+    }
+
+    csp.domain_representation = DomainRepresentation::Values;
+    csp.value_ordering = ValueOrdering::LeastConstrainingValue;
+
+    println!(
+        "  {:<56} {:>12?}",
+        "MinimumRemainingValues, ForwardChecking, LeastConstrainingValue",
+        time(|| csp.backtracking_search())
+    );
+
+    csp.value_ordering = ValueOrdering::DomainOrder;
+    csp.variable_ordering = VariableOrdering::FirstUnassigned;
+    csp.inference = Inference::None;
+
+    println!(
+        "  {:<56} {:>12?}",
+        "every solution",
+        time(|| csp.count_solutions())
+    );
+
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    println!(
+        "  {:<56} {:>12?}",
+        format!("every solution, {threads} threads"),
+        time(|| csp.parallel_count_solutions(threads))
+    );
+}
+
+fn main() {
+    compare("Eight queens", eight_queens());
+    compare("SEND+MORE=MONEY", send_more_money());
+}
@@ -93,7 +93,9 @@ fn main() {
     })
     .unwrap();
 
-    let solution = csp.backtracking_search();
+    let (solution, stats) = csp.backtracking_search_with_stats();
+
+    println!("Search statistics: {stats}");
 
     match solution {
         Some(solution) => {
@@ -102,4 +104,7 @@ fn main() {
         }
         None => println!("No solution found :-("),
     }
+
+    // `solutions()` resumes the search after each board, so counting them never keeps more than one around.
+    println!("There are {} solutions in total.", csp.count_solutions());
 }
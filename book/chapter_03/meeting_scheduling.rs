@@ -0,0 +1,103 @@
+/// # Meeting scheduling, with preferences as soft constraints.
+///
+/// Meetings that share an attendee cannot take place in the same hour; those are hard constraints.
+/// Everything else is a preference with a weight, and the solver looks for the schedule that disappoints people the least.
+use std::collections::HashMap;
+
+use classic_computer_science_problems::csp::{
+    OptimisationOutcome, Relation, SearchConfig, StandardConstraint, CSP,
+};
+
+fn main() {
+    let meetings = vec![
+        "standup",
+        "design review",
+        "one-on-one",
+        "planning",
+        "retro",
+    ];
+    let mut hours = HashMap::<&str, Vec<i32>>::new();
+
+    for meeting in meetings.clone() {
+        hours.insert(meeting, (9..17).collect());
+    }
+
+    let mut csp = CSP::new(meetings, hours).unwrap();
+
+    // Shared attendees.
+    for (first, second) in [
+        ("standup", "design review"),
+        ("standup", "planning"),
+        ("design review", "planning"),
+        ("one-on-one", "planning"),
+        ("planning", "retro"),
+        ("design review", "retro"),
+    ] {
+        csp.add_constraint(StandardConstraint::not_equal(first, second))
+            .expect("valid constraint");
+    }
+
+    // The standup really ought to be first thing.
+    csp.add_soft_constraint(
+        StandardConstraint::linear(vec![(1, "standup")], Relation::Equal, 9),
+        10,
+    )
+    .expect("valid constraint");
+
+    // Nobody wants a retro before lunch.
+    csp.add_soft_constraint(
+        StandardConstraint::linear(vec![(1, "retro")], Relation::GreaterOrEqual, 14),
+        5,
+    )
+    .expect("valid constraint");
+
+    // Planning should come after the design review, and the one-on-one right before planning.
+    csp.add_soft_constraint(
+        StandardConstraint::predicate("design review", "planning", |review, planning| {
+            review < planning
+        }),
+        3,
+    )
+    .expect("valid constraint");
+
+    csp.add_soft_constraint(
+        StandardConstraint::predicate("one-on-one", "planning", |one_on_one, planning| {
+            one_on_one + 1 == *planning
+        }),
+        2,
+    )
+    .expect("valid constraint");
+
+    // The design review is best done in the morning.
+    csp.add_soft_constraint(
+        StandardConstraint::linear(vec![(1, "design review")], Relation::Less, 12),
+        4,
+    )
+    .expect("valid constraint");
+
+    // The manager would like planning done before 11, which does not fit with everything above.
+    csp.add_soft_constraint(
+        StandardConstraint::linear(vec![(1, "planning")], Relation::Less, 11),
+        3,
+    )
+    .expect("valid constraint");
+
+    let outcome = csp.minimise_penalty(SearchConfig::new(), |_, penalty| {
+        println!("Found a schedule with penalty {penalty}");
+    });
+
+    match outcome {
+        OptimisationOutcome::Optimal { solution, cost } => {
+            println!("Best schedule (penalty {cost}):");
+
+            let mut schedule: Vec<_> = solution.into_iter().collect();
+            schedule.sort_by_key(|&(_, hour)| hour);
+
+            for (meeting, hour) in schedule {
+                println!("{hour:>2}:00 {meeting}");
+            }
+        }
+        OptimisationOutcome::Unsatisfiable => println!("No schedule found :-("),
+        OptimisationOutcome::GaveUp { .. } => unreachable!("the search has no limits"),
+    }
+}
@@ -5,7 +5,7 @@
 /// Java implementation: https://github.com/davecom/ClassicComputerScienceProblemsInJava/blob/master/CCSPiJ/src/chapter3/SendMoreMoneyConstraint.java
 use std::collections::{HashMap, HashSet};
 
-use classic_computer_science_problems::csp::{Constraint, CSP};
+use classic_computer_science_problems::csp::{Constraint, Solution, CSP};
 #[derive(Clone, Debug)]
 struct SendMoreMoneyConstraint {
     letters: Vec<char>,
@@ -69,7 +69,7 @@ fn main() {
     match solution {
         Some(solution) => {
             println!("Found solution:");
-            println!("{solution:?}");
+            println!("{}", Solution::from(solution));
         }
         None => println!("No solution found :-("),
     }
@@ -0,0 +1,61 @@
+/// # SEND+MORE=MONEY, using the standard constraints in `csp`.
+///
+/// Instead of hand-rolling a `Constraint` like `send_more_money.rs` does, this version states the puzzle with two ready-made constraints:
+/// every letter is a different digit, and the column sums form one linear equation.
+///
+/// Moving every term to the left-hand side, SEND + MORE - MONEY = 0 becomes
+/// 1000S + 91E - 90N + D - 9000M - 900O + 10R - Y = 0.
+///
+/// Since the linear constraint can only be checked once every letter is assigned, forward checking does most of the work here.
+use std::collections::HashMap;
+
+use classic_computer_science_problems::csp::{
+    Inference, Relation, Solution, StandardConstraint, CSP,
+};
+
+fn main() {
+    let letters = vec!['S', 'E', 'N', 'D', 'M', 'O', 'R', 'Y'];
+    let mut possible_digits = HashMap::<char, Vec<i32>>::new();
+
+    for letter in letters.clone() {
+        possible_digits.insert(letter, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    // So we don't get answers starting with an O.
+    possible_digits.insert('M', vec![1]);
+
+    let mut csp = CSP::new(letters.clone(), possible_digits).unwrap();
+
+    csp.inference = Inference::ForwardChecking;
+
+    csp.add_constraint(StandardConstraint::all_different(letters))
+        .expect("valid constraint");
+
+    csp.add_constraint(StandardConstraint::linear(
+        vec![
+            (1000, 'S'),
+            (91, 'E'),
+            (-90, 'N'),
+            (1, 'D'),
+            (-9000, 'M'),
+            (-900, 'O'),
+            (10, 'R'),
+            (-1, 'Y'),
+        ],
+        Relation::Equal,
+        0,
+    ))
+    .expect("valid constraint");
+
+    let (solution, stats) = csp.backtracking_search_with_stats();
+
+    println!("Search statistics: {stats}");
+
+    match solution {
+        Some(solution) => {
+            println!("Found solution:");
+            println!("{}", Solution::from(solution));
+        }
+        None => println!("No solution found :-("),
+    }
+}
@@ -0,0 +1,117 @@
+/// # Solve a CSP problem file
+///
+/// Reads a problem in JSON (a `.json` file) or in the text format (anything else), solves it and prints the assignment.
+/// See `csp::Problem` for both formats, and `book/chapter_03/problems` for some examples.
+///
+/// By default, this uses the backtracking search from `csp`. With `--pcp`, libpcp solves the problem instead,
+/// and with `--cross-check`, both do, and their answers are checked against each other and against the constraints.
+///
+/// Run this with `cargo run --release --bin solve_csp -- [--pcp | --cross-check] book/chapter_03/problems/send_more_money.csp`.
+use std::collections::HashMap;
+use std::env;
+use std::process;
+
+use classic_computer_science_problems::csp::{Inference, Problem, ProblemCsp, VariableOrdering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    Backtracking,
+    Pcp,
+    CrossCheck,
+}
+
+fn print_solution(csp: &ProblemCsp, solution: &HashMap<String, i64>) {
+    for variable in &csp.variables {
+        println!("{variable} = {}", solution[variable]);
+    }
+}
+
+fn pcp_search(csp: &ProblemCsp) -> Option<HashMap<String, i64>> {
+    match csp.pcp_search() {
+        Ok(solution) => solution,
+        Err(error) => {
+            eprintln!("libpcp cannot solve this problem: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let mut backend = Backend::Backtracking;
+    let mut path = None;
+
+    for argument in env::args().skip(1) {
+        match argument.as_str() {
+            "--pcp" => backend = Backend::Pcp,
+            "--cross-check" => backend = Backend::CrossCheck,
+            _ => path = Some(argument),
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("Usage: solve_csp [--pcp | --cross-check] <problem file>");
+        process::exit(2);
+    };
+
+    let problem = match Problem::from_file(&path) {
+        Ok(problem) => problem,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            process::exit(1);
+        }
+    };
+
+    let mut csp = match problem.to_csp() {
+        Ok(csp) => csp,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            process::exit(1);
+        }
+    };
+
+    // Problem files can be much bigger than the puzzles in the book, so use the stronger search settings.
+    csp.variable_ordering = VariableOrdering::MinimumRemainingValuesThenDegree;
+    csp.inference = Inference::ForwardChecking;
+
+    let solution = match backend {
+        Backend::Backtracking => csp.backtracking_search(),
+        Backend::Pcp => pcp_search(&csp),
+        Backend::CrossCheck => {
+            let backtracking = csp.backtracking_search();
+            let pcp = pcp_search(&csp);
+
+            // The two may well find different solutions, but each must be valid, and they must agree on whether there is one.
+            let agree = match (&backtracking, &pcp) {
+                (Some(first), Some(second)) => csp.is_solution(first) && csp.is_solution(second),
+                (None, None) => true,
+                _ => false,
+            };
+
+            if !agree {
+                println!("The backends disagree!");
+                println!("Backtracking search: {backtracking:?}");
+                println!("libpcp: {pcp:?}");
+                process::exit(1);
+            }
+
+            println!("The backends agree.");
+
+            backtracking
+        }
+    };
+
+    match solution {
+        Some(solution) => print_solution(&csp, &solution),
+        None => {
+            println!("No solution found :-(");
+
+            if let Some(conflict) = csp.minimal_unsatisfiable_subset() {
+                println!("These constraints cannot all be satisfied:");
+
+                for constraint in conflict {
+                    println!("    {constraint:?}");
+                }
+            }
+        }
+    }
+}
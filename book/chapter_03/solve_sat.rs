@@ -0,0 +1,74 @@
+/// # Solve SAT
+///
+/// Reads a boolean formula in DIMACS CNF, e.g., a benchmark from SATLIB, and solves it as a `csp::SatCsp`.
+/// The answer is printed the way SAT solvers do, with an `s` line saying whether the formula is satisfiable
+/// and `v` lines giving the assignment, and the exit code is 10 if it is and 20 if it is not.
+///
+/// By default, this uses conflict-driven clause learning. With `--backtracking`, it uses the backtracking search from `csp` instead,
+/// which is much slower on big formulas, but makes for a good cross-check on small ones.
+///
+/// Run this with `cargo run --release --bin solve_sat -- [--backtracking] book/chapter_03/problems/four_queens.cnf`.
+use std::env;
+use std::process;
+
+use classic_computer_science_problems::csp::{Backtracking, Cnf, Inference, VariableOrdering};
+
+fn main() {
+    let mut backtracking = false;
+    let mut path = None;
+
+    for argument in env::args().skip(1) {
+        match argument.as_str() {
+            "--backtracking" => backtracking = true,
+            _ => path = Some(argument),
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("Usage: solve_sat [--backtracking] <DIMACS CNF file>");
+        process::exit(2);
+    };
+
+    let cnf = match Cnf::from_file(&path) {
+        Ok(cnf) => cnf,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            process::exit(1);
+        }
+    };
+
+    let mut csp = match cnf.to_csp() {
+        Ok(csp) => csp,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            process::exit(1);
+        }
+    };
+
+    let (solution, stats) = if backtracking {
+        // Forward checking on a clause is unit propagation, which makes this DPLL, plus backjumping.
+        csp.variable_ordering = VariableOrdering::MinimumRemainingValuesThenDegree;
+        csp.inference = Inference::ForwardChecking;
+        csp.backtracking = Backtracking::ConflictDirected {
+            record_nogoods: true,
+        };
+
+        csp.backtracking_search_with_stats()
+    } else {
+        csp.cdcl_search_with_stats()
+    };
+
+    println!("c {stats}");
+
+    match solution {
+        Some(solution) => {
+            println!("s SATISFIABLE");
+            println!("{}", cnf.solution_to_dimacs(&solution));
+            process::exit(10);
+        }
+        None => {
+            println!("s UNSATISFIABLE");
+            process::exit(20);
+        }
+    }
+}
@@ -291,7 +291,9 @@ fn main() {
     csp.add_constraint(WordSearchConstraint { words })
         .expect("valid constraint");
 
-    let solution = csp.backtracking_search();
+    let (solution, stats) = csp.backtracking_search_with_stats();
+
+    println!("Search statistics: {stats}");
 
     match solution {
         Some(mut solution) => {
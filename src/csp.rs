@@ -1,10 +1,204 @@
 use std::collections::*;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{fmt, hash::Hash};
 
+mod backjumping;
+mod constraints;
+mod domain;
+mod engine;
+mod explain;
+mod format;
+mod local_search;
+mod optimisation;
+mod parallel;
+mod pcp_backend;
+mod sat;
+mod soft;
+mod solution;
+mod symmetry;
+
+pub use constraints::{BinaryPredicate, Relation, StandardConstraint};
+pub use domain::{BitSetDomain, Domain, DomainRepresentation, IntervalDomain, ValuesDomain};
+pub use format::{ConstraintSpec, DomainSpec, Problem, ProblemCsp, ProblemError, VariableSpec};
+pub use local_search::{LocalSearchConfig, LocalSearchResult, LocalSearchStrategy};
+pub use optimisation::{Objective, OptimisationOutcome};
+pub use pcp_backend::PcpError;
+pub use sat::{Clause, Cnf, SatCsp};
+pub use soft::SoftConstraint;
+pub use solution::Solution;
+pub use symmetry::Symmetry;
+
+use backjumping::Backjumping;
+use engine::{Checkpoint, Model, SearchState};
+use symmetry::SymmetryBreaking;
+
 pub trait Constraint<V, D> {
     fn variables(&self) -> Vec<V>;
     // to be implemented by the problem at-hand, e.g., Australian map colouring problem
     fn is_satisfied(&self, assignment: &HashMap<V, D>) -> bool;
+
+    /// The number of variables the constraint itself expects, if that is fixed.
+    /// `CSP::add_constraint` rejects the constraint if this disagrees with `variables()`.
+    fn arity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Any other check that the constraint is well formed, e.g., that every tuple of a table constraint has one value per variable.
+    /// `CSP::add_constraint` rejects the constraint if this fails.
+    fn validate(&self) -> Result<(), CspError<V>> {
+        Ok(())
+    }
+}
+
+/// # CSP Error
+///
+/// Why a problem definition was rejected by `CSP::new` or `CSP::add_constraint`.
+/// Where a particular variable is at fault, the error carries it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CspError<V> {
+    /// A variable has no entry in the domains.
+    MissingDomain(V),
+    /// A variable's domain has no values, so the problem cannot have a solution.
+    EmptyDomain(V),
+    /// A variable appears more than once in the list of variables.
+    DuplicateVariable(V),
+    /// A constraint involves a variable that is not part of the CSP.
+    UnknownVariable(V),
+    /// A constraint involves the same variable more than once.
+    RepeatedConstraintVariable(V),
+    /// A constraint involves no variables at all.
+    EmptyConstraint,
+    /// A constraint involves a different number of variables than it expects.
+    ArityMismatch { expected: usize, found: usize },
+    /// Tuple number `tuple` (from 0) of a table constraint has `found` values instead of one for each of its `expected` variables.
+    TupleLength {
+        tuple: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A symmetry does not swap variables or values one for one, lists an interchangeable value more than once,
+    /// or names a value that is in no domain.
+    InvalidSymmetry,
+}
+
+impl<V> fmt::Display for CspError<V>
+where
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CspError::MissingDomain(variable) => {
+                write!(f, "variable {variable:?} has no domain assigned to it")
+            }
+            CspError::EmptyDomain(variable) => {
+                write!(f, "variable {variable:?} has an empty domain")
+            }
+            CspError::DuplicateVariable(variable) => {
+                write!(f, "variable {variable:?} is listed more than once")
+            }
+            CspError::UnknownVariable(variable) => {
+                write!(
+                    f,
+                    "constraint involves variable {variable:?}, which is not in the CSP"
+                )
+            }
+            CspError::RepeatedConstraintVariable(variable) => {
+                write!(
+                    f,
+                    "constraint involves variable {variable:?} more than once"
+                )
+            }
+            CspError::EmptyConstraint => write!(f, "constraint involves no variables"),
+            CspError::ArityMismatch { expected, found } => write!(
+                f,
+                "constraint expects {expected} variables but involves {found}"
+            ),
+            CspError::TupleLength {
+                tuple,
+                expected,
+                found,
+            } => write!(
+                f,
+                "tuple {tuple} of the table has {found} values, but the table has {expected} variables"
+            ),
+            CspError::InvalidSymmetry => {
+                write!(f, "symmetry does not map known values one for one")
+            }
+        }
+    }
+}
+
+impl<V> std::error::Error for CspError<V> where V: fmt::Debug {}
+
+/// # Variable Ordering
+///
+/// Strategy the backtracking search uses to pick the next unassigned variable.
+///
+/// The book always takes the first unassigned variable, which is fine for small problems, but picking the
+/// most constrained variable first tends to reach dead ends much earlier and therefore prunes far more of the search tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VariableOrdering {
+    /// Take variables in the order they were given to `CSP::new`.
+    #[default]
+    FirstUnassigned,
+    /// Minimum remaining values: pick the variable with the fewest values left that are consistent with the assignment.
+    MinimumRemainingValues,
+    /// Degree heuristic: pick the variable involved in the most constraints with other unassigned variables.
+    Degree,
+    /// Minimum remaining values, breaking ties with the degree heuristic.
+    MinimumRemainingValuesThenDegree,
+}
+
+/// # Value Ordering
+///
+/// The order in which the backtracking search tries the values of the variable it picked.
+///
+/// Variable ordering wants to fail early, but value ordering wants to succeed early:
+/// when looking for one solution, a value that leaves the other variables more options is more likely to lead to one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValueOrdering {
+    /// Take values in the order of the variable's domain, as the book does.
+    #[default]
+    DomainOrder,
+    /// Least constraining value: try first the value that rules out the fewest values of the other unassigned variables
+    /// it shares a constraint with. Ties keep the order of the domain.
+    LeastConstrainingValue,
+}
+
+/// # Inference
+///
+/// How much the backtracking search prunes the domains of unassigned variables after each assignment.
+///
+/// Pruning lets the search notice a dead end as soon as some variable has no values left,
+/// rather than only once it tries to assign that variable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Inference {
+    /// Only check constraints on the variable just assigned, as the book does.
+    #[default]
+    None,
+    /// Forward checking: prune values of neighbouring variables that conflict with the new assignment.
+    ForwardChecking,
+    /// Maintaining arc consistency: forward checking, then AC-3 propagation over binary constraints.
+    MaintainArcConsistency,
+}
+
+/// # Backtracking
+///
+/// Where the backtracking search goes back to once a variable has run out of values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backtracking {
+    /// Back to the previous variable, as the book does.
+    #[default]
+    Chronological,
+    /// Conflict-directed backjumping: straight back to the latest variable whose assignment helped rule out those values,
+    /// skipping every variable in between, since trying other values for them would only fail the same way again.
+    ///
+    /// With `record_nogoods`, the values behind each such dead end are also remembered as a nogood,
+    /// a combination the search never tries again, even after a variable ordering heuristic has rearranged things.
+    /// That trades memory for time on problems where the same conflict turns up in many branches.
+    ConflictDirected { record_nogoods: bool },
 }
 
 #[derive(Debug)]
@@ -15,6 +209,15 @@ where
     pub variables: Vec<V>,
     pub domains: HashMap<V, Vec<D>>,
     pub constraints: HashMap<V, Vec<C>>,
+    pub variable_ordering: VariableOrdering,
+    pub value_ordering: ValueOrdering,
+    pub inference: Inference,
+    pub backtracking: Backtracking,
+    /// How the search keeps track of the values left in each domain. `domains` itself is always a `Vec<D>` per variable.
+    pub domain_representation: DomainRepresentation<D>,
+    /// Preferences rather than requirements. Only `minimise_penalty` looks at these.
+    pub soft_constraints: Vec<SoftConstraint<C>>,
+    symmetry: SymmetryBreaking<V, D>,
 }
 
 impl<V, D, C> CSP<V, D, C>
@@ -23,14 +226,20 @@ where
     D: Clone + fmt::Debug,
     C: Clone + Constraint<V, D>,
 {
-    pub fn new(variables: Vec<V>, domains: HashMap<V, Vec<D>>) -> Result<Self, &'static str> {
+    pub fn new(variables: Vec<V>, domains: HashMap<V, Vec<D>>) -> Result<Self, CspError<V>> {
         let mut constraints = HashMap::<V, Vec<C>>::new();
 
         for variable in &variables {
-            constraints.insert(variable.clone(), Vec::new());
+            if constraints.insert(variable.clone(), Vec::new()).is_some() {
+                return Err(CspError::DuplicateVariable(variable.clone()));
+            }
 
-            if !domains.contains_key(variable) {
-                return Err("Every variable should have a doamin assigned to it.");
+            match domains.get(variable) {
+                None => return Err(CspError::MissingDomain(variable.clone())),
+                Some(domain) if domain.is_empty() => {
+                    return Err(CspError::EmptyDomain(variable.clone()))
+                }
+                Some(_) => {}
             }
         }
 
@@ -38,16 +247,21 @@ where
             variables,
             domains,
             constraints,
+            variable_ordering: VariableOrdering::default(),
+            value_ordering: ValueOrdering::default(),
+            inference: Inference::default(),
+            backtracking: Backtracking::default(),
+            domain_representation: DomainRepresentation::default(),
+            soft_constraints: Vec::new(),
+            symmetry: SymmetryBreaking::default(),
         })
     }
 
-    pub fn add_constraint(&mut self, constraint: C) -> Result<(), &'static str> {
-        for variable in constraint.variables() {
-            if !self.variables.contains(&variable) {
-                return Err("Variable in constraint is not in CSP");
-            }
+    pub fn add_constraint(&mut self, constraint: C) -> Result<(), CspError<V>> {
+        let variables = self.validate_constraint(&constraint)?;
 
-            if let Some(constraints) = self.constraints.get_mut(&variable) {
+        for variable in &variables {
+            if let Some(constraints) = self.constraints.get_mut(variable) {
                 constraints.push(constraint.clone());
             }
         }
@@ -55,54 +269,694 @@ where
         Ok(())
     }
 
-    fn is_consistent(&self, variable: V, assignment: &HashMap<V, D>) -> bool {
-        for constraints in self.constraints.get(&variable) {
-            for constraint in constraints {
-                if !constraint.is_satisfied(&assignment) {
-                    return false;
-                }
+    /// Every constraint added with `add_constraint`, once each.
+    /// A constraint is registered under every variable it involves, so it is only taken from the list of its first variable.
+    fn registered_constraints(&self) -> impl Iterator<Item = &C> {
+        self.variables.iter().flat_map(|variable| {
+            self.constraints
+                .get(variable)
+                .into_iter()
+                .flatten()
+                .filter(move |constraint| constraint.variables().first() == Some(variable))
+        })
+    }
+
+    /// Check that `constraint` only involves known variables, each of them once, and return those variables.
+    fn validate_constraint(&self, constraint: &C) -> Result<Vec<V>, CspError<V>> {
+        let variables = constraint.variables();
+
+        if variables.is_empty() {
+            return Err(CspError::EmptyConstraint);
+        }
+
+        if let Some(expected) = constraint.arity() {
+            if expected != variables.len() {
+                return Err(CspError::ArityMismatch {
+                    expected,
+                    found: variables.len(),
+                });
             }
         }
 
-        true
+        constraint.validate()?;
+
+        // Validate everything before registering anything, so a rejected constraint leaves the CSP untouched.
+        for (index, variable) in variables.iter().enumerate() {
+            if !self.constraints.contains_key(variable) {
+                return Err(CspError::UnknownVariable(variable.clone()));
+            }
+
+            if variables[..index].contains(variable) {
+                return Err(CspError::RepeatedConstraintVariable(variable.clone()));
+            }
+        }
+
+        Ok(variables)
+    }
+
+    /// # Make Arc Consistent
+    ///
+    /// Run AC-3 over every binary constraint, permanently removing unsupported values from `self.domains`.
+    /// This is a cheap preprocessing step before searching.
+    ///
+    /// Returns `false` if some domain was wiped out, in which case the problem has no solution.
+    pub fn make_arc_consistent(&mut self) -> bool {
+        let model = Model::new(self);
+        let mut state = model.state(HashMap::new());
+
+        let consistent = model
+            .make_arc_consistent(&mut state, &mut SearchStats::default())
+            .is_ok();
+        let domains = model.live_domains(&state);
+
+        self.domains = domains;
+
+        consistent
+    }
+
+    /// # Solutions
+    ///
+    /// Lazily enumerate every solution that extends `assignment`.
+    /// Each call to `next()` resumes the search where the previous solution was found,
+    /// so `take(n)` only does as much work as is needed to find `n` solutions.
+    pub fn solutions_with_assignment(&self, assignment: HashMap<V, D>) -> Solutions<'_, V, D, C> {
+        Solutions::new(self, assignment)
+    }
+
+    /// Lazily enumerate every solution of this CSP. See `solutions_with_assignment`.
+    pub fn solutions(&self) -> Solutions<'_, V, D, C> {
+        self.solutions_with_assignment(HashMap::new())
+    }
+
+    /// Whether `assignment` gives every variable a value from its domain and satisfies every constraint.
+    /// Handy for checking a solution that came from somewhere else, e.g., another solver.
+    pub fn is_solution(&self, assignment: &HashMap<V, D>) -> bool
+    where
+        D: PartialEq,
+    {
+        self.variables.iter().all(|variable| {
+            match (assignment.get(variable), self.domains.get(variable)) {
+                (Some(value), Some(domain)) => domain.contains(value),
+                _ => false,
+            }
+        }) && self
+            .registered_constraints()
+            .all(|constraint| constraint.is_satisfied(assignment))
+    }
+
+    /// Count every solution of this CSP without keeping them around.
+    pub fn count_solutions(&self) -> usize {
+        self.solutions().count()
+    }
+
+    /// Find the first solution, along with statistics on how much searching it took.
+    pub fn backtracking_search_with_stats(&self) -> (Option<HashMap<V, D>>, SearchStats) {
+        let mut solutions = self.solutions();
+        let solution = solutions.next();
+
+        (solution, solutions.stats())
+    }
+
+    /// Find the first solution, reporting every step of the search to `observer`.
+    pub fn backtracking_search_with_observer<O>(&self, observer: &mut O) -> Option<HashMap<V, D>>
+    where
+        O: SearchObserver<V, D>,
+    {
+        self.solutions().with_observer(observer).next()
+    }
+
+    /// Find the first solution, giving up once any limit in `config` is reached.
+    pub fn backtracking_search_with_config(&self, config: SearchConfig) -> SearchOutcome<V, D> {
+        let mut solutions = self.solutions().with_config(config);
+
+        match solutions.next() {
+            Some(solution) => SearchOutcome::Solved(solution),
+            None => match solutions.stopped() {
+                Some(reason) => SearchOutcome::GaveUp(reason),
+                None => SearchOutcome::Unsatisfiable,
+            },
+        }
     }
 
     pub fn backtracking_search_with_assignment(
         &self,
         assignment: HashMap<V, D>,
     ) -> Option<HashMap<V, D>> {
-        // Assignment is complete if every variable is assigned (base case)
-        if assignment.len() == self.variables.len() {
-            return Some(assignment);
+        self.solutions_with_assignment(assignment).next()
+    }
+
+    /// # Backtracking Search
+    ///
+    /// Find the first solution, or `None` if there is none. Sort it with `Solution::from` to print or compare it.
+    ///
+    /// The same problem always gives the same first solution, from one run to the next and one machine to the next:
+    /// the search goes through variables and values in the order of `variables` and of each domain, never in the order of a `HashMap`.
+    /// Only changing the search settings, or the order of the variables, values or constraints, can change which solution comes first.
+    pub fn backtracking_search(&self) -> Option<HashMap<V, D>> {
+        self.backtracking_search_with_assignment(HashMap::new())
+    }
+}
+
+/// # Search Statistics
+///
+/// How much work a backtracking search did. Handy for comparing variable orderings and inference modes on the same problem.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Values assigned to a variable, i.e., nodes of the search tree visited.
+    pub nodes: u64,
+    /// Times a variable ran out of values and the search went back to the previous variable.
+    pub backtracks: u64,
+    /// Calls to `Constraint::is_satisfied`, including those made while pruning domains.
+    pub constraint_checks: u64,
+    /// Most variables assigned at once.
+    pub max_depth: usize,
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes, {} backtracks, {} constraint checks, max depth {}",
+            self.nodes, self.backtracks, self.constraint_checks, self.max_depth
+        )
+    }
+}
+
+/// # Search Observer
+///
+/// Hooks called as the backtracking search walks the search tree, e.g., to log or draw it.
+/// Every method does nothing by default, so implement only the events you care about.
+///
+/// `depth` is the number of variables assigned, counting the one the event is about.
+pub trait SearchObserver<V, D> {
+    /// `variable` has just been given `value`, before it is checked against the constraints.
+    fn on_assign(&mut self, _variable: &V, _value: &D, _depth: usize) {}
+    /// The value of `variable` has just been taken back, because it failed, to try the next one,
+    /// or because backjumping skipped over it.
+    fn on_unassign(&mut self, _variable: &V, _depth: usize) {}
+    /// Every value of `variable` has been tried, so the search goes back to an earlier variable.
+    fn on_backtrack(&mut self, _variable: &V, _depth: usize) {}
+    /// The search found a solution.
+    fn on_solution(&mut self, _solution: &HashMap<V, D>) {}
+}
+
+impl<V, D, O> SearchObserver<V, D> for &mut O
+where
+    O: SearchObserver<V, D>,
+{
+    fn on_assign(&mut self, variable: &V, value: &D, depth: usize) {
+        (**self).on_assign(variable, value, depth);
+    }
+
+    fn on_unassign(&mut self, variable: &V, depth: usize) {
+        (**self).on_unassign(variable, depth);
+    }
+
+    fn on_backtrack(&mut self, variable: &V, depth: usize) {
+        (**self).on_backtrack(variable, depth);
+    }
+
+    fn on_solution(&mut self, solution: &HashMap<V, D>) {
+        (**self).on_solution(solution);
+    }
+}
+
+/// # Cancellation Token
+///
+/// Lets one thread ask a search running on another to stop. Clone the token, hand one copy to `SearchConfig`,
+/// and call `cancel()` on the other; the search notices before it visits its next node.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+}
+
+/// # Search Config
+///
+/// Bounds on how long a backtracking search may run. Every limit is off by default.
+#[derive(Clone, Debug, Default)]
+pub struct SearchConfig {
+    /// Give up once this instant has passed.
+    pub deadline: Option<Instant>,
+    /// Give up after visiting this many nodes (see `SearchStats::nodes`).
+    pub max_nodes: Option<u64>,
+    /// Give up once this token is cancelled.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl SearchConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Give up `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
         }
+    }
 
-        let unassigned = self.variables.iter().find(|v| !assignment.contains_key(v));
+    pub fn with_max_nodes(self, max_nodes: u64) -> Self {
+        Self {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
 
-        if let Some(unassigned) = unassigned {
-            for domains in self.domains.get(unassigned) {
-                for value in domains {
-                    let mut local_assignment = assignment.clone();
+    pub fn with_cancellation(self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self
+        }
+    }
 
-                    local_assignment.insert(unassigned.clone(), value.clone());
+    /// Which limit, if any, a search with these `stats` has hit.
+    fn limit_reached(&self, stats: &SearchStats) -> Option<StopReason> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(StopReason::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(StopReason::Deadline)
+        } else if self
+            .max_nodes
+            .is_some_and(|max_nodes| stats.nodes >= max_nodes)
+        {
+            Some(StopReason::NodeLimit)
+        } else {
+            None
+        }
+    }
+}
 
-                    if self.is_consistent(unassigned.clone(), &local_assignment) {
-                        // Return a search result ONLY on `Some`.
-                        // Returning `result` itself is completely valid per the types,
-                        // but this can cause invalid states to be returned as well.
-                        if let Some(result) =
-                            self.backtracking_search_with_assignment(local_assignment.clone())
-                        {
-                            return Some(result);
-                        }
-                    }
+/// Which limit from a `SearchConfig` stopped a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Deadline,
+    NodeLimit,
+    Cancelled,
+}
+
+/// # Search Outcome
+///
+/// The result of a search that may be cut short. Unlike the `Option` from `backtracking_search`,
+/// this tells "there is no solution" apart from "the search stopped before it could tell".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchOutcome<V, D>
+where
+    V: Hash + Eq,
+{
+    Solved(HashMap<V, D>),
+    /// The whole search space was explored without finding a solution.
+    Unsatisfiable,
+    /// The search hit one of its limits first.
+    GaveUp(StopReason),
+}
+
+/// A choice point in the search: the variable being tried (by its index in `CSP::variables`),
+/// the indices of its values in the order to try them, how many of those have been tried,
+/// and the trail from before any of its values were assigned.
+#[derive(Clone, Debug)]
+struct Frame {
+    variable: usize,
+    values: Vec<usize>,
+    next_value: usize,
+    checkpoint: Checkpoint,
+    // Whether backjumping must treat every earlier variable as a culprit, because something besides the constraints
+    // cut this part of the search short, i.e., a solution was found or a branch was pruned, maybe for symmetry.
+    chronological: bool,
+}
+
+/// Decides whether a partial assignment is worth extending. See `Solutions::with_pruning`.
+type Pruning<'a, V, D> = Box<dyn FnMut(&HashMap<V, D>) -> bool + 'a>;
+
+/// # Solutions
+///
+/// Iterator over the solutions of a `CSP`, created by `CSP::solutions`.
+///
+/// The recursive backtracking search from the book cannot be paused once it finds a solution,
+/// so this keeps its own stack of choice points instead. Popping a frame is the equivalent of returning from a recursive call.
+///
+/// Trying a value never copies the assignment or the domains: there is one of each, changed in place and undone on backtracking.
+///
+/// Like `CSP::backtracking_search`, it finds the same solutions in the same order every time.
+pub struct Solutions<'a, V, D, C>
+where
+    C: Constraint<V, D>,
+{
+    model: Model<'a, V, D, C>,
+    state: SearchState<V, D>,
+    stack: Vec<Frame>,
+    stats: SearchStats,
+    observer: Option<Box<dyn SearchObserver<V, D> + 'a>>,
+    config: SearchConfig,
+    stopped: Option<StopReason>,
+    prune: Option<Pruning<'a, V, D>>,
+    backjumping: Option<Backjumping>,
+    // The first call to `next()` has to set up the root of the search tree rather than resume it.
+    // Until then, this holds whether inference over the initial assignment left any hope of a solution.
+    root: Option<bool>,
+}
+
+impl<'a, V, D, C> Solutions<'a, V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    fn new(csp: &'a CSP<V, D, C>, assignment: HashMap<V, D>) -> Self {
+        let mut stats = SearchStats {
+            max_depth: assignment.len(),
+            ..Default::default()
+        };
+
+        let model = Model::new(csp);
+        let mut state = model.state(assignment);
+        let root = model.infer_initial(&mut state, &mut stats)
+            && model.allowed_by_symmetries(&state.assignment);
+
+        let backjumping = match csp.backtracking {
+            Backtracking::Chronological => None,
+            Backtracking::ConflictDirected { record_nogoods } => {
+                Some(Backjumping::new(csp.variables.len(), record_nogoods))
+            }
+        };
+
+        Self {
+            model,
+            state,
+            stack: Vec::new(),
+            stats,
+            observer: None,
+            config: SearchConfig::default(),
+            stopped: None,
+            prune: None,
+            backjumping,
+            root: Some(root),
+        }
+    }
+
+    /// Report every step of the search to `observer` from now on.
+    pub fn with_observer<O>(mut self, observer: O) -> Self
+    where
+        O: SearchObserver<V, D> + 'a,
+    {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Cut off every branch for which `prune` returns `true`, e.g., because it cannot lead to a better solution than one already found.
+    /// `prune` sees each consistent partial assignment, after inference, before the search goes any deeper.
+    pub fn with_pruning<F>(mut self, prune: F) -> Self
+    where
+        F: FnMut(&HashMap<V, D>) -> bool + 'a,
+    {
+        self.prune = Some(Box::new(prune));
+        self
+    }
+
+    /// Stop the search once any limit in `config` is reached. Check `stopped()` to tell this apart from running out of solutions.
+    pub fn with_config(mut self, config: SearchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Which limit stopped the search, if it stopped early.
+    pub fn stopped(&self) -> Option<StopReason> {
+        self.stopped
+    }
+
+    /// Statistics for the search so far, across every solution found.
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    fn found_solution(&mut self) -> HashMap<V, D> {
+        // Every variable on the stack is now part of a solution, so none of them may be jumped over.
+        for frame in &mut self.stack {
+            frame.chronological = true;
+        }
+
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_solution(&self.state.assignment);
+        }
+
+        self.state.assignment.clone()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.state.assignment.len() == self.model.csp.variables.len()
+    }
+
+    /// Open a choice point for the next variable to assign.
+    fn push_frame(&mut self) {
+        if let Some(variable) = self
+            .model
+            .select_unassigned_variable(&mut self.state, &mut self.stats)
+        {
+            if let Some(backjumping) = self.backjumping.as_mut() {
+                backjumping.open(variable);
+            }
+
+            let values = self
+                .model
+                .value_order(variable, &mut self.state, &mut self.stats);
+
+            self.stack.push(Frame {
+                variable,
+                values,
+                next_value: 0,
+                checkpoint: self.state.checkpoint(),
+                chronological: false,
+            });
+        }
+    }
+
+    /// The variable on top of the stack has run out of values, so go back to an earlier one.
+    fn backtrack(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+
+        let depth = self.state.assignment.len();
+
+        self.stats.backtracks += 1;
+
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_backtrack(&self.model.csp.variables[frame.variable], depth);
+        }
+
+        let Some(backjumping) = self.backjumping.as_mut() else {
+            return;
+        };
+
+        // The variables that ruled out a value, whether by a constraint, a nogood or by pruning the domain.
+        // Variables given in the initial assignment are never on the stack, so they are left out.
+        let mut conflicts = backjumping.take_conflicts(frame.variable);
+
+        for &culprit in self.state.pruned_by(frame.variable) {
+            if !conflicts.contains(&culprit) {
+                conflicts.push(culprit);
+            }
+        }
+
+        conflicts.retain(|&culprit| self.stack.iter().any(|other| other.variable == culprit));
+
+        let target = if frame.chronological {
+            self.stack.len().checked_sub(1)
+        } else {
+            backjumping.record_nogood(&conflicts);
+
+            self.stack
+                .iter()
+                .rposition(|other| conflicts.contains(&other.variable))
+        };
+
+        let Some(target) = target else {
+            // Nothing on the stack can be changed to get around the conflict, so there are no more solutions.
+            self.stack.clear();
+            return;
+        };
+
+        while self.stack.len() > target + 1 {
+            if let Some(skipped) = self.stack.pop() {
+                let name = &self.model.csp.variables[skipped.variable];
+                let depth = self.state.assignment.len();
+
+                self.state.assigned[skipped.variable] = false;
+                self.state.assignment.remove(name);
+
+                if let Some(observer) = self.observer.as_mut() {
+                    observer.on_unassign(name, depth);
                 }
             }
         }
 
-        None
+        if let Some(target) = self.stack.last_mut() {
+            target.chronological |= frame.chronological;
+            backjumping.blame(target.variable, conflicts);
+        }
     }
+}
 
-    pub fn backtracking_search(&self) -> Option<HashMap<V, D>> {
-        self.backtracking_search_with_assignment(HashMap::new())
+impl<'a, V, D, C> Iterator for Solutions<'a, V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    type Item = HashMap<V, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(consistent) = self.root.take() {
+            if !consistent {
+                return None;
+            }
+
+            // Assignment is complete if every variable is assigned (base case)
+            if self.is_complete() {
+                return Some(self.found_solution());
+            }
+
+            self.push_frame();
+        }
+
+        while let Some(frame) = self.stack.last_mut() {
+            if self.stopped.is_some() {
+                break;
+            }
+
+            let variable = frame.variable;
+            let name = &self.model.csp.variables[variable];
+
+            // Undo whatever value this variable had on the previous pass, along with everything inference pruned because of it.
+            self.state.undo(frame.checkpoint);
+
+            let depth = self.state.assignment.len();
+
+            if self.state.assigned[variable] {
+                self.state.assigned[variable] = false;
+                self.state.assignment.remove(name);
+
+                if let Some(observer) = self.observer.as_mut() {
+                    observer.on_unassign(name, depth);
+                }
+            }
+
+            let domain = self.model.domains[variable];
+            let next_value = (frame.next_value..frame.values.len())
+                .find(|&position| self.state.is_live(variable, frame.values[position]));
+
+            let Some(position) = next_value else {
+                // Every value failed, so backtrack.
+                self.backtrack();
+                continue;
+            };
+
+            let index = frame.values[position];
+
+            frame.next_value = position + 1;
+
+            if let Some(reason) = self.config.limit_reached(&self.stats) {
+                self.stopped = Some(reason);
+                break;
+            }
+
+            let value = &domain[index];
+
+            self.state.assigned[variable] = true;
+            self.state.assignment.insert(name.clone(), value.clone());
+
+            let depth = self.state.assignment.len();
+
+            self.stats.nodes += 1;
+            self.stats.max_depth = self.stats.max_depth.max(depth);
+
+            if let Some(observer) = self.observer.as_mut() {
+                observer.on_assign(name, value, depth);
+            }
+
+            if let Some(backjumping) = self.backjumping.as_mut() {
+                backjumping.assign(variable, index);
+            }
+
+            if let Some(scope) =
+                self.model
+                    .violated_constraint(variable, &self.state.assignment, &mut self.stats)
+            {
+                if let Some(backjumping) = self.backjumping.as_mut() {
+                    let assigned = &self.state.assigned;
+
+                    backjumping.blame(
+                        variable,
+                        scope.iter().copied().filter(|&other| assigned[other]),
+                    );
+                }
+
+                continue;
+            }
+
+            if let Some(backjumping) = self.backjumping.as_mut() {
+                if !backjumping.check_nogoods(variable, &self.state.assigned) {
+                    continue;
+                }
+            }
+
+            if !self.model.allowed_by_symmetries(&self.state.assignment) {
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.chronological = true;
+                }
+
+                continue;
+            }
+
+            if let Err(wiped_out) = self.model.infer(variable, &mut self.state, &mut self.stats) {
+                if let Some(backjumping) = self.backjumping.as_mut() {
+                    backjumping.blame(variable, self.state.pruned_by(wiped_out).iter().copied());
+                }
+
+                continue;
+            }
+
+            if let Some(prune) = self.prune.as_mut() {
+                if prune(&self.state.assignment) {
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.chronological = true;
+                    }
+
+                    continue;
+                }
+            }
+
+            if self.is_complete() {
+                // Leave this frame on the stack, so the next call carries on with the next value.
+                return Some(self.found_solution());
+            }
+
+            self.push_frame();
+        }
+
+        None
     }
 }
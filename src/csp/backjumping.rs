@@ -0,0 +1,108 @@
+/// # Backjumping
+///
+/// The bookkeeping for conflict-directed backjumping, by variable index like the rest of the search engine.
+///
+/// Each variable on the stack has a conflict set: the earlier variables whose assignments ruled out one of its values.
+/// Once every value is gone, the search jumps straight back to the latest variable in that set,
+/// since changing anything assigned after it cannot help.
+pub(super) struct Backjumping {
+    conflicts: Vec<Vec<usize>>,
+    /// The index of each assigned variable's value in its domain.
+    values: Vec<usize>,
+    nogoods: Option<Nogoods>,
+}
+
+/// Combinations of values that the search has proven cannot be part of a solution.
+struct Nogoods {
+    /// Each nogood as `(variable, value index)` pairs.
+    literals: Vec<Vec<(usize, usize)>>,
+    /// For each variable, the nogoods it appears in.
+    watches: Vec<Vec<usize>>,
+}
+
+impl Backjumping {
+    pub fn new(variables: usize, record_nogoods: bool) -> Self {
+        Self {
+            conflicts: vec![Vec::new(); variables],
+            values: vec![0; variables],
+            nogoods: record_nogoods.then(|| Nogoods {
+                literals: Vec::new(),
+                watches: vec![Vec::new(); variables],
+            }),
+        }
+    }
+
+    /// A variable is about to be tried, so nothing has been ruled out for it yet.
+    pub fn open(&mut self, variable: usize) {
+        self.conflicts[variable].clear();
+    }
+
+    pub fn assign(&mut self, variable: usize, value: usize) {
+        self.values[variable] = value;
+    }
+
+    /// Add `culprits` to the conflict set of `variable`.
+    pub fn blame(&mut self, variable: usize, culprits: impl IntoIterator<Item = usize>) {
+        for culprit in culprits {
+            if culprit != variable && !self.conflicts[variable].contains(&culprit) {
+                self.conflicts[variable].push(culprit);
+            }
+        }
+    }
+
+    /// Take the conflict set of a variable that has run out of values.
+    pub fn take_conflicts(&mut self, variable: usize) -> Vec<usize> {
+        std::mem::take(&mut self.conflicts[variable])
+    }
+
+    /// Check the value just given to `variable` against the recorded nogoods.
+    /// If it completes one, the other variables in it are blamed and this returns `false`.
+    pub fn check_nogoods(&mut self, variable: usize, assigned: &[bool]) -> bool {
+        let Some(nogoods) = self.nogoods.as_ref() else {
+            return true;
+        };
+
+        let violated = nogoods.watches[variable].iter().find(|&&nogood| {
+            nogoods.literals[nogood]
+                .iter()
+                .all(|&(other, value)| assigned[other] && self.values[other] == value)
+        });
+
+        let Some(&nogood) = violated else {
+            return true;
+        };
+
+        let culprits: Vec<usize> = nogoods.literals[nogood]
+            .iter()
+            .map(|&(other, _)| other)
+            .collect();
+
+        self.blame(variable, culprits);
+
+        false
+    }
+
+    /// Remember that the current values of `variables` cannot all be part of a solution.
+    pub fn record_nogood(&mut self, variables: &[usize]) {
+        let Some(nogoods) = self.nogoods.as_mut() else {
+            return;
+        };
+
+        if variables.is_empty() {
+            return;
+        }
+
+        let index = nogoods.literals.len();
+
+        nogoods.literals.push(
+            variables
+                .iter()
+                .map(|&variable| (variable, self.values[variable]))
+                .collect(),
+        );
+
+        for &variable in variables {
+            nogoods.watches[variable].push(index);
+        }
+    }
+}
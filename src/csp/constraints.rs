@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::{fmt, hash::Hash};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Constraint, CspError};
+
+/// # Relation
+///
+/// How the left-hand side of a linear constraint compares to its constant.
+/// Problem files write these as the usual symbols, e.g., `"<="`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Relation {
+    #[serde(rename = "=")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+}
+
+impl Relation {
+    pub fn holds<T>(self, lhs: T, rhs: T) -> bool
+    where
+        T: Ord,
+    {
+        match self {
+            Relation::Equal => lhs == rhs,
+            Relation::NotEqual => lhs != rhs,
+            Relation::Less => lhs < rhs,
+            Relation::LessOrEqual => lhs <= rhs,
+            Relation::Greater => lhs > rhs,
+            Relation::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
+
+/// A binary predicate over the values of two variables, shareable between clones of a constraint and across threads.
+pub type BinaryPredicate<D> = Arc<dyn Fn(&D, &D) -> bool + Send + Sync>;
+
+/// # Standard Constraints
+///
+/// Ready-made constraints, so that a new problem does not need its own `Constraint` implementation.
+/// They are all variants of one type, so one `CSP<V, D, StandardConstraint<V, D>>` can mix them freely,
+/// and `Custom` lets a hand-written constraint sit alongside them.
+///
+/// Like the constraints in the book, each one is considered satisfied until enough of its variables are assigned to tell otherwise,
+/// so they can be checked against partial assignments during the backtracking search.
+///
+/// Prefer the constructor functions, e.g., `StandardConstraint::not_equal("WA", "NT")`, over building the variants directly.
+#[derive(Clone)]
+pub enum StandardConstraint<V, D> {
+    /// No two of the variables have the same value.
+    AllDifferent(Vec<V>),
+    /// The two variables have different values.
+    NotEqual(V, V),
+    /// `predicate` holds for the values of the two variables, in that order.
+    Predicate {
+        variables: (V, V),
+        predicate: BinaryPredicate<D>,
+    },
+    /// The sum of `coefficient * variable` over `terms` stands in `relation` to `constant`.
+    /// `to_integer` reads a value as an integer; `StandardConstraint::linear` fills it in.
+    /// The sum is worked out exactly, so large coefficients cannot wrap around.
+    Linear {
+        terms: Vec<(i64, V)>,
+        relation: Relation,
+        constant: i64,
+        to_integer: fn(&D) -> i64,
+    },
+    /// The values of the variables, in order, are one of the allowed `tuples`.
+    Table {
+        variables: Vec<V>,
+        tuples: Vec<Vec<D>>,
+    },
+    /// Any other constraint.
+    Custom(Arc<dyn Constraint<V, D> + Send + Sync>),
+}
+
+impl<V, D> StandardConstraint<V, D> {
+    pub fn all_different(variables: Vec<V>) -> Self {
+        Self::AllDifferent(variables)
+    }
+
+    pub fn not_equal(first: V, second: V) -> Self {
+        Self::NotEqual(first, second)
+    }
+
+    /// A binary constraint from a closure, e.g., `StandardConstraint::predicate('A', 'B', |a, b| a < b)`.
+    pub fn predicate<F>(first: V, second: V, predicate: F) -> Self
+    where
+        F: Fn(&D, &D) -> bool + Send + Sync + 'static,
+    {
+        Self::Predicate {
+            variables: (first, second),
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// `sum(coefficient * variable) <relation> constant` over integer variables.
+    /// A variable may appear in several terms; its coefficients are simply added up.
+    pub fn linear(terms: Vec<(i64, V)>, relation: Relation, constant: i64) -> Self
+    where
+        D: Copy + Into<i64>,
+    {
+        Self::Linear {
+            terms,
+            relation,
+            constant,
+            to_integer: |value| (*value).into(),
+        }
+    }
+
+    pub fn table(variables: Vec<V>, tuples: Vec<Vec<D>>) -> Self {
+        Self::Table { variables, tuples }
+    }
+
+    pub fn custom<C>(constraint: C) -> Self
+    where
+        C: Constraint<V, D> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(constraint))
+    }
+}
+
+impl<V, D> Constraint<V, D> for StandardConstraint<V, D>
+where
+    V: Clone + Hash + Eq,
+    D: PartialEq,
+{
+    fn variables(&self) -> Vec<V> {
+        match self {
+            Self::AllDifferent(variables) => variables.clone(),
+            Self::NotEqual(first, second) => vec![first.clone(), second.clone()],
+            Self::Predicate {
+                variables: (first, second),
+                ..
+            } => vec![first.clone(), second.clone()],
+            Self::Linear { terms, .. } => {
+                let mut variables: Vec<V> = Vec::new();
+
+                for (_, variable) in terms {
+                    if !variables.contains(variable) {
+                        variables.push(variable.clone());
+                    }
+                }
+
+                variables
+            }
+            Self::Table { variables, .. } => variables.clone(),
+            Self::Custom(constraint) => constraint.variables(),
+        }
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<V, D>) -> bool {
+        match self {
+            Self::AllDifferent(variables) => {
+                let values: Vec<&D> = variables
+                    .iter()
+                    .filter_map(|variable| assignment.get(variable))
+                    .collect();
+
+                values
+                    .iter()
+                    .enumerate()
+                    .all(|(i, value)| !values[i + 1..].contains(value))
+            }
+            Self::NotEqual(first, second) => {
+                match (assignment.get(first), assignment.get(second)) {
+                    (Some(first), Some(second)) => first != second,
+                    _ => true,
+                }
+            }
+            Self::Predicate {
+                variables: (first, second),
+                predicate,
+            } => match (assignment.get(first), assignment.get(second)) {
+                (Some(first), Some(second)) => predicate(first, second),
+                _ => true,
+            },
+            Self::Linear {
+                terms,
+                relation,
+                constant,
+                to_integer,
+            } => {
+                // In `i128`, where a coefficient times a value cannot overflow, nor can any realistic number of them added up.
+                let mut sum: i128 = 0;
+
+                for (coefficient, variable) in terms {
+                    match assignment.get(variable) {
+                        Some(value) => {
+                            let term = i128::from(*coefficient) * i128::from(to_integer(value));
+
+                            match sum.checked_add(term) {
+                                Some(total) => sum = total,
+                                // Far beyond any `constant`, but which way depends on the sign, so count it as violated.
+                                None => return false,
+                            }
+                        }
+                        None => return true,
+                    }
+                }
+
+                relation.holds(sum, i128::from(*constant))
+            }
+            Self::Table { variables, tuples } => {
+                // Any assigned variables must agree with at least one allowed tuple.
+                tuples.iter().any(|tuple| {
+                    variables.iter().zip(tuple).all(|(variable, allowed)| {
+                        assignment
+                            .get(variable)
+                            .is_none_or(|value| value == allowed)
+                    })
+                })
+            }
+            Self::Custom(constraint) => constraint.is_satisfied(assignment),
+        }
+    }
+
+    fn arity(&self) -> Option<usize> {
+        match self {
+            Self::Custom(constraint) => constraint.arity(),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), CspError<V>> {
+        match self {
+            Self::Table { variables, tuples } => {
+                match tuples
+                    .iter()
+                    .position(|tuple| tuple.len() != variables.len())
+                {
+                    Some(tuple) => Err(CspError::TupleLength {
+                        tuple,
+                        expected: variables.len(),
+                        found: tuples[tuple].len(),
+                    }),
+                    None => Ok(()),
+                }
+            }
+            Self::Custom(constraint) => constraint.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<V, D> fmt::Debug for StandardConstraint<V, D>
+where
+    V: fmt::Debug,
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AllDifferent(variables) => {
+                f.debug_tuple("AllDifferent").field(variables).finish()
+            }
+            Self::NotEqual(first, second) => f
+                .debug_tuple("NotEqual")
+                .field(first)
+                .field(second)
+                .finish(),
+            Self::Predicate { variables, .. } => f
+                .debug_struct("Predicate")
+                .field("variables", variables)
+                .finish_non_exhaustive(),
+            Self::Linear {
+                terms,
+                relation,
+                constant,
+                ..
+            } => f
+                .debug_struct("Linear")
+                .field("terms", terms)
+                .field("relation", relation)
+                .field("constant", constant)
+                .finish_non_exhaustive(),
+            Self::Table { variables, tuples } => f
+                .debug_struct("Table")
+                .field("variables", variables)
+                .field("tuples", tuples)
+                .finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use bitvec::prelude::*;
+
+/// # Domain
+///
+/// The values a variable has left during a search, as pruned by inference.
+///
+/// A domain starts out holding every value given to `CSP::new` for its variable,
+/// and refers to each value by its position in that `Vec<D>`, so the search never has to compare or hash values.
+/// Values are only ever removed by pruning and restored in the reverse order when the search backtracks.
+pub trait Domain {
+    /// How many values are left. This is what the minimum remaining values heuristic looks at.
+    fn size(&self) -> usize;
+
+    fn contains(&self, index: usize) -> bool;
+
+    /// Remove a value that is still in the domain.
+    fn remove(&mut self, index: usize);
+
+    /// Put back a value that was removed.
+    fn restore(&mut self, index: usize);
+}
+
+/// # Domain Representation
+///
+/// Which `Domain` the search uses for every variable.
+///
+/// The integer representations read each value as an integer with a function,
+/// which `DomainRepresentation::bit_set` and `DomainRepresentation::interval` fill in.
+/// They store each integer once, so values that read as the same integer are live or pruned together.
+#[derive(Default)]
+pub enum DomainRepresentation<D> {
+    /// A flag for each value in the `Vec<D>` given to `CSP::new`. This works for values of any type.
+    #[default]
+    Values,
+    /// A bit for each integer between the smallest and largest value, for small, dense integer domains like digits or board positions.
+    /// A domain too sparse for a bit set, e.g., `{0, 1_000_000_000}`, is stored as an `IntervalDomain` instead.
+    BitSet(fn(&D) -> i64),
+    /// Disjoint runs of integers, for wide integer domains that are mostly pruned in runs, e.g., from either end by a linear constraint.
+    Interval(fn(&D) -> i64),
+}
+
+impl<D> DomainRepresentation<D> {
+    pub fn bit_set() -> Self
+    where
+        D: Copy + Into<i64>,
+    {
+        Self::BitSet(|value| (*value).into())
+    }
+
+    pub fn interval() -> Self
+    where
+        D: Copy + Into<i64>,
+    {
+        Self::Interval(|value| (*value).into())
+    }
+
+    /// A domain holding all of `values`.
+    pub fn domain(&self, values: &[D]) -> Box<dyn Domain + Send> {
+        match self {
+            Self::Values => Box::new(ValuesDomain::new(values.len())),
+            Self::BitSet(to_integer) => {
+                let integers: Vec<i64> = values.iter().map(to_integer).collect();
+
+                match BitSetDomain::new(integers.clone()) {
+                    Some(domain) => Box::new(domain),
+                    None => Box::new(IntervalDomain::new(integers)),
+                }
+            }
+            Self::Interval(to_integer) => {
+                Box::new(IntervalDomain::new(values.iter().map(to_integer).collect()))
+            }
+        }
+    }
+}
+
+impl<D> Clone for DomainRepresentation<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for DomainRepresentation<D> {}
+
+impl<D> fmt::Debug for DomainRepresentation<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Values => f.write_str("Values"),
+            Self::BitSet(_) => f.debug_tuple("BitSet").finish_non_exhaustive(),
+            Self::Interval(_) => f.debug_tuple("Interval").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// # Values Domain
+///
+/// A flag for each value, saying whether it is still live.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValuesDomain {
+    live: Vec<bool>,
+    size: usize,
+}
+
+impl ValuesDomain {
+    pub fn new(len: usize) -> Self {
+        Self {
+            live: vec![true; len],
+            size: len,
+        }
+    }
+}
+
+impl Domain for ValuesDomain {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.live[index]
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.live[index] = false;
+        self.size -= 1;
+    }
+
+    fn restore(&mut self, index: usize) {
+        self.live[index] = true;
+        self.size += 1;
+    }
+}
+
+/// # Bit Set Domain
+///
+/// A set of integers stored as one bit for each integer from the smallest value to the largest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSetDomain {
+    /// Each value, as an offset from the smallest one.
+    offsets: Vec<usize>,
+    bits: BitVec,
+    size: usize,
+}
+
+impl BitSetDomain {
+    /// A bit set may have this many bits for each value before it counts as too sparse.
+    pub const MAX_BITS_PER_VALUE: usize = 64;
+
+    /// A domain holding all of `values`,
+    /// or `None` if they are spread out over more than `MAX_BITS_PER_VALUE` integers each.
+    pub fn new(values: Vec<i64>) -> Option<Self> {
+        let min = values.iter().copied().min().unwrap_or_default();
+        let max = values.iter().copied().max().unwrap_or_default();
+
+        // In `i128`, where `max - min` cannot overflow even for `i64::MIN` and `i64::MAX`.
+        let span = i128::from(max) - i128::from(min) + 1;
+
+        if span > (values.len().max(1) * Self::MAX_BITS_PER_VALUE) as i128 {
+            return None;
+        }
+
+        let offsets: Vec<usize> = values
+            .iter()
+            .map(|&value| (i128::from(value) - i128::from(min)) as usize)
+            .collect();
+
+        let mut bits = bitvec![0; span as usize];
+
+        for &offset in &offsets {
+            bits.set(offset, true);
+        }
+
+        let size = bits.count_ones();
+
+        Some(Self {
+            offsets,
+            bits,
+            size,
+        })
+    }
+}
+
+impl Domain for BitSetDomain {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.bits[self.offsets[index]]
+    }
+
+    // Values that share an integer share a bit, so only the first of them to be removed or restored changes the size.
+    fn remove(&mut self, index: usize) {
+        if self.bits.replace(self.offsets[index], false) {
+            self.size -= 1;
+        }
+    }
+
+    fn restore(&mut self, index: usize) {
+        if !self.bits.replace(self.offsets[index], true) {
+            self.size += 1;
+        }
+    }
+}
+
+/// # Interval Domain
+///
+/// A set of integers stored as the disjoint intervals it is made of, e.g., `[0..3][7..9]`.
+///
+/// Removing a value shrinks or splits the interval it is in, and restoring it grows or joins them again, all in place.
+/// Only a split needs room for another interval, and once there is room for it, backtracking leaves that room behind for next time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalDomain {
+    values: Vec<i64>,
+    /// The live intervals as `(lower, upper)`, both included, in increasing order and never touching.
+    runs: Vec<(i64, i64)>,
+    size: usize,
+}
+
+impl IntervalDomain {
+    pub fn new(values: Vec<i64>) -> Self {
+        let runs = runs(&values);
+
+        let size = runs
+            .iter()
+            .map(|&(lower, upper)| (i128::from(upper) - i128::from(lower) + 1) as usize)
+            .sum();
+
+        Self { values, runs, size }
+    }
+
+    /// The position of the first interval that does not lie wholly below `value`.
+    fn position(&self, value: i64) -> usize {
+        self.runs.partition_point(|&(_, upper)| upper < value)
+    }
+}
+
+/// The runs of consecutive integers in `values`, in increasing order, e.g., `[(0, 3), (7, 9)]`.
+/// Building an interval set from these takes one step, rather than one union per value.
+pub(super) fn runs(values: &[i64]) -> Vec<(i64, i64)> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut runs: Vec<(i64, i64)> = Vec::new();
+
+    for value in sorted {
+        match runs.last_mut() {
+            Some((_, upper)) if upper.checked_add(1) == Some(value) => *upper = value,
+            _ => runs.push((value, value)),
+        }
+    }
+
+    runs
+}
+
+impl Domain for IntervalDomain {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        let value = self.values[index];
+
+        self.runs
+            .get(self.position(value))
+            .is_some_and(|&(lower, _)| lower <= value)
+    }
+
+    fn remove(&mut self, index: usize) {
+        let value = self.values[index];
+        let position = self.position(value);
+        let (lower, upper) = self.runs[position];
+
+        if lower == upper {
+            self.runs.remove(position);
+        } else if value == lower {
+            self.runs[position].0 = value + 1;
+        } else if value == upper {
+            self.runs[position].1 = value - 1;
+        } else {
+            self.runs[position].1 = value - 1;
+            self.runs.insert(position + 1, (value + 1, upper));
+        }
+
+        self.size -= 1;
+    }
+
+    fn restore(&mut self, index: usize) {
+        let value = self.values[index];
+        let position = self.position(value);
+
+        // `value` is not in any interval, so it lies strictly between the one before `position` and the one at it.
+        let joins_previous = position > 0 && self.runs[position - 1].1 == value - 1;
+        let joins_next = position < self.runs.len() && self.runs[position].0 == value + 1;
+
+        match (joins_previous, joins_next) {
+            (true, true) => {
+                self.runs[position - 1].1 = self.runs[position].1;
+                self.runs.remove(position);
+            }
+            (true, false) => self.runs[position - 1].1 = value,
+            (false, true) => self.runs[position].0 = value,
+            (false, false) => self.runs.insert(position, (value, value)),
+        }
+
+        self.size += 1;
+    }
+}
@@ -0,0 +1,635 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::{fmt, hash::Hash};
+
+use super::{
+    Backtracking, Constraint, Domain, Inference, SearchStats, ValueOrdering, VariableOrdering, CSP,
+};
+
+/// # Model
+///
+/// A CSP with every variable replaced by its position in `CSP::variables`.
+/// It is built once per search, so the inner loop indexes into vectors
+/// instead of hashing variables and calling `Constraint::variables` over and over.
+pub(super) struct Model<'a, V, D, C>
+where
+    C: Constraint<V, D>,
+{
+    pub csp: &'a CSP<V, D, C>,
+    pub domains: Vec<&'a [D]>,
+    /// For each variable, its constraints along with the variables each of them involves.
+    constraints: Vec<Vec<(&'a C, Vec<usize>)>>,
+    /// For each variable, the variables it shares a binary constraint with, along with those constraints.
+    neighbours: Vec<Vec<(usize, Vec<&'a C>)>>,
+}
+
+/// # Search State
+///
+/// The single assignment a search works on, and the domains as pruned by inference.
+///
+/// Pruning a value only removes it from the variable's `Domain` and records it on a trail,
+/// so backtracking undoes exactly what was pruned since a choice point instead of restoring a copy of every domain.
+///
+/// For conflict-directed backjumping, it also keeps track of which assigned variables caused each domain to shrink.
+pub(super) struct SearchState<V, D> {
+    pub assignment: HashMap<V, D>,
+    pub assigned: Vec<bool>,
+    live: Vec<Box<dyn Domain + Send>>,
+    trail: Vec<(usize, usize)>,
+    explain: bool,
+    pruned_by: Vec<Vec<usize>>,
+    // The variables whose `pruned_by` grew, in order, so that can be undone like the trail.
+    reasons: Vec<usize>,
+    // Scratch space for inference, kept between calls so it does not have to be allocated again.
+    queue: VecDeque<(usize, usize)>,
+    touched: Vec<usize>,
+}
+
+/// How far the trail of a `SearchState` had got at some point, so it can be undone back to there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Checkpoint {
+    trail: usize,
+    reasons: usize,
+}
+
+impl<V, D> SearchState<V, D> {
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            trail: self.trail.len(),
+            reasons: self.reasons.len(),
+        }
+    }
+
+    /// Bring back every value pruned since `checkpoint`.
+    pub fn undo(&mut self, checkpoint: Checkpoint) {
+        while self.trail.len() > checkpoint.trail {
+            if let Some((variable, index)) = self.trail.pop() {
+                self.live[variable].restore(index);
+            }
+        }
+
+        while self.reasons.len() > checkpoint.reasons {
+            if let Some(variable) = self.reasons.pop() {
+                self.pruned_by[variable].pop();
+            }
+        }
+    }
+
+    /// The assigned variables responsible for the values pruned from `variable`'s domain.
+    /// Only tracked for conflict-directed backjumping; otherwise always empty.
+    pub fn pruned_by(&self, variable: usize) -> &[usize] {
+        &self.pruned_by[variable]
+    }
+
+    /// Note that the assignment of `culprit` helped prune values from `variable`'s domain.
+    fn blame(&mut self, variable: usize, culprit: usize) {
+        if self.explain && !self.pruned_by[variable].contains(&culprit) {
+            self.pruned_by[variable].push(culprit);
+            self.reasons.push(variable);
+        }
+    }
+
+    pub fn is_live(&self, variable: usize, index: usize) -> bool {
+        self.live[variable].contains(index)
+    }
+
+    fn prune(&mut self, variable: usize, index: usize) {
+        self.live[variable].remove(index);
+        self.trail.push((variable, index));
+    }
+}
+
+impl<'a, V, D, C> Model<'a, V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    pub fn new(csp: &'a CSP<V, D, C>) -> Self {
+        let positions: HashMap<&V, usize> = csp
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (variable, index))
+            .collect();
+
+        let domains = csp
+            .variables
+            .iter()
+            .map(|variable| csp.domains.get(variable).map_or(&[][..], Vec::as_slice))
+            .collect();
+
+        let constraints: Vec<Vec<(&C, Vec<usize>)>> = csp
+            .variables
+            .iter()
+            .map(|variable| {
+                csp.constraints
+                    .get(variable)
+                    .into_iter()
+                    .flatten()
+                    .map(|constraint| {
+                        let scope = constraint
+                            .variables()
+                            .iter()
+                            .filter_map(|other| positions.get(other).copied())
+                            .collect();
+
+                        (constraint, scope)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let neighbours = constraints
+            .iter()
+            .enumerate()
+            .map(|(x, constraints)| {
+                let mut neighbours: Vec<(usize, Vec<&C>)> = Vec::new();
+
+                for (constraint, scope) in constraints {
+                    let &[first, second] = scope.as_slice() else {
+                        continue;
+                    };
+
+                    let y = if first == x { second } else { first };
+
+                    match neighbours.iter_mut().find(|(other, _)| *other == y) {
+                        Some((_, between)) => between.push(constraint),
+                        None => neighbours.push((y, vec![constraint])),
+                    }
+                }
+
+                neighbours
+            })
+            .collect();
+
+        Self {
+            csp,
+            domains,
+            constraints,
+            neighbours,
+        }
+    }
+
+    /// A fresh state for a search that starts from `assignment`, with nothing pruned yet.
+    pub fn state(&self, assignment: HashMap<V, D>) -> SearchState<V, D> {
+        SearchState {
+            assigned: self
+                .csp
+                .variables
+                .iter()
+                .map(|variable| assignment.contains_key(variable))
+                .collect(),
+            assignment,
+            live: self
+                .domains
+                .iter()
+                .map(|domain| self.csp.domain_representation.domain(domain))
+                .collect(),
+            trail: Vec::new(),
+            explain: self.csp.backtracking != Backtracking::Chronological,
+            pruned_by: vec![Vec::new(); self.domains.len()],
+            reasons: Vec::new(),
+            queue: VecDeque::new(),
+            touched: Vec::new(),
+        }
+    }
+
+    /// The values of every variable that have not been pruned, keyed by variable.
+    pub fn live_domains(&self, state: &SearchState<V, D>) -> HashMap<V, Vec<D>> {
+        self.csp
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(x, variable)| {
+                let values = self.domains[x]
+                    .iter()
+                    .enumerate()
+                    .filter(|&(index, _)| state.is_live(x, index))
+                    .map(|(_, value)| value.clone())
+                    .collect();
+
+                (variable.clone(), values)
+            })
+            .collect()
+    }
+
+    fn is_consistent(
+        &self,
+        variable: usize,
+        assignment: &HashMap<V, D>,
+        stats: &mut SearchStats,
+    ) -> bool {
+        self.violated_constraint(variable, assignment, stats)
+            .is_none()
+    }
+
+    /// The variables involved in the first constraint on `variable` that `assignment` violates, if any.
+    pub fn violated_constraint(
+        &self,
+        variable: usize,
+        assignment: &HashMap<V, D>,
+        stats: &mut SearchStats,
+    ) -> Option<&[usize]> {
+        self.constraints[variable]
+            .iter()
+            .find(|(constraint, _)| {
+                stats.constraint_checks += 1;
+
+                !constraint.is_satisfied(assignment)
+            })
+            .map(|(_, scope)| scope.as_slice())
+    }
+
+    /// Count the values in `variable`'s domain that are consistent with the assignment.
+    ///
+    /// When inference is enabled, the domains have already been pruned against the assignment,
+    /// so the size of the domain is the answer.
+    fn remaining_values(
+        &self,
+        variable: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> usize {
+        if self.csp.inference != Inference::None {
+            return state.live[variable].size();
+        }
+
+        let name = &self.csp.variables[variable];
+        let mut count = 0;
+
+        for (index, value) in self.domains[variable].iter().enumerate() {
+            if !state.is_live(variable, index) {
+                continue;
+            }
+
+            state.assignment.insert(name.clone(), value.clone());
+
+            if self.is_consistent(variable, &state.assignment, stats) {
+                count += 1;
+            }
+        }
+
+        state.assignment.remove(name);
+
+        count
+    }
+
+    /// Count the constraints on `variable` that also involve at least one other unassigned variable.
+    fn degree(&self, variable: usize, state: &SearchState<V, D>) -> usize {
+        self.constraints[variable]
+            .iter()
+            .filter(|(_, scope)| {
+                scope
+                    .iter()
+                    .any(|&other| other != variable && !state.assigned[other])
+            })
+            .count()
+    }
+
+    /// Pick the next variable to assign according to `CSP::variable_ordering`.
+    /// Ties are always broken by the order of `CSP::variables`.
+    pub fn select_unassigned_variable(
+        &self,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Option<usize> {
+        let mut best: Option<((usize, Reverse<usize>), usize)> = None;
+
+        for variable in 0..self.domains.len() {
+            if state.assigned[variable] {
+                continue;
+            }
+
+            let key = match self.csp.variable_ordering {
+                VariableOrdering::FirstUnassigned => return Some(variable),
+                VariableOrdering::MinimumRemainingValues => {
+                    (self.remaining_values(variable, state, stats), Reverse(0))
+                }
+                VariableOrdering::Degree => (0, Reverse(self.degree(variable, state))),
+                VariableOrdering::MinimumRemainingValuesThenDegree => (
+                    self.remaining_values(variable, state, stats),
+                    Reverse(self.degree(variable, state)),
+                ),
+            };
+
+            if best.as_ref().is_none_or(|(best_key, _)| key < *best_key) {
+                best = Some((key, variable));
+            }
+        }
+
+        best.map(|(_, variable)| variable)
+    }
+
+    /// Count the values of the other unassigned variables in `variable`'s constraints
+    /// that giving `variable` the value at `index` would rule out.
+    fn ruled_out(
+        &self,
+        variable: usize,
+        index: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> usize {
+        let name = &self.csp.variables[variable];
+        let mut count = 0;
+
+        state
+            .assignment
+            .insert(name.clone(), self.domains[variable][index].clone());
+
+        for (constraint, scope) in &self.constraints[variable] {
+            for &other in scope {
+                if other == variable || state.assigned[other] {
+                    continue;
+                }
+
+                let other_name = &self.csp.variables[other];
+
+                for (other_index, value) in self.domains[other].iter().enumerate() {
+                    if !state.is_live(other, other_index) {
+                        continue;
+                    }
+
+                    state.assignment.insert(other_name.clone(), value.clone());
+                    stats.constraint_checks += 1;
+
+                    if !constraint.is_satisfied(&state.assignment) {
+                        count += 1;
+                    }
+                }
+
+                state.assignment.remove(other_name);
+            }
+        }
+
+        state.assignment.remove(name);
+
+        count
+    }
+
+    /// The indices of the values left in `variable`'s domain, in the order `CSP::value_ordering` wants them tried.
+    pub fn value_order(
+        &self,
+        variable: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Vec<usize> {
+        let mut values: Vec<usize> = (0..self.domains[variable].len())
+            .filter(|&index| state.is_live(variable, index))
+            .collect();
+
+        if self.csp.value_ordering == ValueOrdering::LeastConstrainingValue {
+            // The sort is stable, so ties keep the order of the domain.
+            values.sort_by_cached_key(|&index| self.ruled_out(variable, index, state, stats));
+        }
+
+        values
+    }
+
+    /// Whether `assignment` survives symmetry breaking. See `Symmetry`.
+    pub fn allowed_by_symmetries(&self, assignment: &HashMap<V, D>) -> bool {
+        self.csp
+            .symmetry
+            .allows(&self.csp.variables, &self.domains, assignment)
+    }
+
+    /// # Forward Checking
+    ///
+    /// Having just assigned `variable`, prune from the domains of the other unassigned variables in its constraints
+    /// every value that would violate one of those constraints.
+    /// The variables whose domains shrank are added to `state.touched`.
+    ///
+    /// Returns the variable whose domain was wiped out, if any.
+    fn forward_check(
+        &self,
+        variable: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Result<(), usize> {
+        for (constraint, scope) in &self.constraints[variable] {
+            for &other in scope {
+                if other == variable || state.assigned[other] {
+                    continue;
+                }
+
+                let name = &self.csp.variables[other];
+                let size_before = state.live[other].size();
+
+                for (index, value) in self.domains[other].iter().enumerate() {
+                    if !state.is_live(other, index) {
+                        continue;
+                    }
+
+                    state.assignment.insert(name.clone(), value.clone());
+                    stats.constraint_checks += 1;
+
+                    if !constraint.is_satisfied(&state.assignment) {
+                        state.prune(other, index);
+                    }
+                }
+
+                state.assignment.remove(name);
+
+                if state.live[other].size() < size_before {
+                    // Every assigned variable of the constraint had a hand in it.
+                    for &culprit in scope {
+                        if state.assigned[culprit] {
+                            state.blame(other, culprit);
+                        }
+                    }
+
+                    if !state.touched.contains(&other) {
+                        state.touched.push(other);
+                    }
+                }
+
+                if state.live[other].size() == 0 {
+                    return Err(other);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prune every value of `x` that has no support in the domain of `y` under the binary constraints between them.
+    /// Returns `true` if the domain of `x` changed.
+    fn revise(
+        &self,
+        x: usize,
+        y: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> bool {
+        let Some((_, constraints)) = self.neighbours[x].iter().find(|(other, _)| *other == y)
+        else {
+            return false;
+        };
+
+        let (x_name, y_name) = (&self.csp.variables[x], &self.csp.variables[y]);
+        let size_before = state.live[x].size();
+
+        let mut all_satisfied = |assignment: &HashMap<V, D>| {
+            constraints.iter().all(|constraint| {
+                stats.constraint_checks += 1;
+
+                constraint.is_satisfied(assignment)
+            })
+        };
+
+        for (index, value) in self.domains[x].iter().enumerate() {
+            if !state.is_live(x, index) {
+                continue;
+            }
+
+            state.assignment.insert(x_name.clone(), value.clone());
+
+            // An assigned `y` supports `x` with its value alone.
+            let supported = if state.assigned[y] {
+                all_satisfied(&state.assignment)
+            } else {
+                let mut supported = false;
+
+                for (support_index, support) in self.domains[y].iter().enumerate() {
+                    if !state.is_live(y, support_index) {
+                        continue;
+                    }
+
+                    state.assignment.insert(y_name.clone(), support.clone());
+
+                    if all_satisfied(&state.assignment) {
+                        supported = true;
+                        break;
+                    }
+                }
+
+                supported
+            };
+
+            if !supported {
+                state.prune(x, index);
+            }
+        }
+
+        state.assignment.remove(x_name);
+
+        if !state.assigned[y] {
+            state.assignment.remove(y_name);
+        }
+
+        if state.live[x].size() == size_before {
+            return false;
+        }
+
+        // The values of `x` lost their support because of `y`'s value, or whatever pruned `y`'s domain.
+        if state.assigned[y] {
+            state.blame(x, y);
+        }
+
+        for index in 0..state.pruned_by[y].len() {
+            state.blame(x, state.pruned_by[y][index]);
+        }
+
+        true
+    }
+
+    /// # AC-3
+    ///
+    /// Make every arc in `state.queue` (and every arc affected by pruning along the way) consistent.
+    /// Only binary constraints take part; constraints over more variables are left to forward checking.
+    ///
+    /// Returns the variable whose domain was wiped out, if any.
+    fn arc_consistency(
+        &self,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Result<(), usize> {
+        while let Some((x, y)) = state.queue.pop_front() {
+            // An assigned variable keeps its value.
+            if state.assigned[x] {
+                continue;
+            }
+
+            if self.revise(x, y, state, stats) {
+                if state.live[x].size() == 0 {
+                    state.queue.clear();
+                    return Err(x);
+                }
+
+                for &(z, _) in &self.neighbours[x] {
+                    if z != y {
+                        state.queue.push_back((z, x));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queue every arc `(x, y)` for each binary constraint between `x` and `y`, then run AC-3.
+    pub fn make_arc_consistent(
+        &self,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Result<(), usize> {
+        state.queue.clear();
+
+        for (x, neighbours) in self.neighbours.iter().enumerate() {
+            for &(y, _) in neighbours {
+                state.queue.push_back((x, y));
+            }
+        }
+
+        self.arc_consistency(state, stats)
+    }
+
+    /// Run `CSP::inference` after assigning `variable`.
+    /// On a dead end, returns the variable whose domain was wiped out.
+    pub fn infer(
+        &self,
+        variable: usize,
+        state: &mut SearchState<V, D>,
+        stats: &mut SearchStats,
+    ) -> Result<(), usize> {
+        state.touched.clear();
+
+        match self.csp.inference {
+            Inference::None => Ok(()),
+            Inference::ForwardChecking => self.forward_check(variable, state, stats),
+            Inference::MaintainArcConsistency => {
+                self.forward_check(variable, state, stats)?;
+
+                state.queue.clear();
+
+                for &y in &state.touched {
+                    for &(x, _) in &self.neighbours[y] {
+                        state.queue.push_back((x, y));
+                    }
+                }
+
+                self.arc_consistency(state, stats)
+            }
+        }
+    }
+
+    /// Run `CSP::inference` over the variables a search starts out with.
+    ///
+    /// Returns `false` if inference already proves there is no solution.
+    pub fn infer_initial(&self, state: &mut SearchState<V, D>, stats: &mut SearchStats) -> bool {
+        if self.csp.inference == Inference::None {
+            return true;
+        }
+
+        for variable in 0..self.domains.len() {
+            state.touched.clear();
+
+            if state.assigned[variable] && self.forward_check(variable, state, stats).is_err() {
+                return false;
+            }
+        }
+
+        self.csp.inference != Inference::MaintainArcConsistency
+            || self.make_arc_consistent(state, stats).is_ok()
+    }
+}
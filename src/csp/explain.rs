@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::{fmt, hash::Hash};
+
+use super::{Constraint, SymmetryBreaking, CSP};
+
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    /// # Minimal Unsatisfiable Subset
+    ///
+    /// Explain why this CSP has no solution: find constraints that cannot all be satisfied together,
+    /// but could be if any one of them were dropped. Returns `None` if there is a solution after all.
+    ///
+    /// For a map colouring with too few colours, this would be the constraints around some region that cannot be coloured,
+    /// rather than every border in the country.
+    ///
+    /// The subset is found with QuickXplain, which solves sub-problems made of some of the constraints,
+    /// using the same variable ordering, inference and backtracking as this CSP.
+    /// The subset is minimal, but there may be other, smaller ones.
+    pub fn minimal_unsatisfiable_subset(&self) -> Option<Vec<C>> {
+        let constraints: Vec<C> = self.registered_constraints().cloned().collect();
+
+        if self.is_satisfiable_with(&constraints) {
+            return None;
+        }
+
+        Some(self.quick_xplain(&[], false, &constraints))
+    }
+
+    /// Whether the variables and domains of this CSP have a solution under `constraints` alone.
+    fn is_satisfiable_with(&self, constraints: &[C]) -> bool {
+        let mut csp = CSP {
+            variables: self.variables.clone(),
+            domains: self.domains.clone(),
+            constraints: self
+                .variables
+                .iter()
+                .map(|variable| (variable.clone(), Vec::new()))
+                .collect::<HashMap<_, _>>(),
+            variable_ordering: self.variable_ordering,
+            value_ordering: self.value_ordering,
+            inference: self.inference,
+            backtracking: self.backtracking,
+            domain_representation: self.domain_representation,
+            soft_constraints: Vec::new(),
+            // A symmetry of the whole problem need not be one of the smaller problem, so breaking it could lose solutions.
+            symmetry: SymmetryBreaking::default(),
+        };
+
+        for constraint in constraints {
+            // These were all accepted by `add_constraint` on this very CSP already.
+            let _ = csp.add_constraint(constraint.clone());
+        }
+
+        csp.backtracking_search().is_some()
+    }
+
+    /// # QuickXplain
+    ///
+    /// Find a minimal subset of `candidates` that is unsatisfiable together with `background`,
+    /// assuming `background` plus all of `candidates` is unsatisfiable.
+    ///
+    /// `background_changed` says whether the caller just added to `background`;
+    /// if it did and `background` is unsatisfiable by itself, none of the candidates are needed.
+    /// Otherwise, split the candidates in half and explain each half in terms of the other.
+    fn quick_xplain(&self, background: &[C], background_changed: bool, candidates: &[C]) -> Vec<C> {
+        if background_changed && !self.is_satisfiable_with(background) {
+            return Vec::new();
+        }
+
+        if candidates.len() <= 1 {
+            return candidates.to_vec();
+        }
+
+        let (first, second) = candidates.split_at(candidates.len() / 2);
+
+        let with_first = [background, first].concat();
+        let from_second = self.quick_xplain(&with_first, !first.is_empty(), second);
+
+        let with_second = [background, &from_second].concat();
+        let from_first = self.quick_xplain(&with_second, !from_second.is_empty(), first);
+
+        [from_first, from_second].concat()
+    }
+}
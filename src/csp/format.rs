@@ -0,0 +1,487 @@
+use std::{fmt, fs, io, path::Path};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, alphanumeric1, char, digit1, one_of, space0, space1},
+    combinator::*,
+    multi::*,
+    sequence::*,
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{CspError, Relation, StandardConstraint, CSP};
+
+/// The kind of CSP a problem file describes: variables named by strings, taking integer values.
+pub type ProblemCsp = CSP<String, i64, StandardConstraint<String, i64>>;
+
+/// # Problem
+///
+/// A CSP written down as data rather than Rust, so that it can be read from a file.
+/// Variables are named and take integer values. Constraints are the standard ones, except for predicates and custom constraints, which need code.
+///
+/// A problem can be written in JSON:
+///
+/// ```json
+/// {
+///   "variables": [
+///     { "name": "A", "domain": { "min": 1, "max": 3 } },
+///     { "name": "B", "domain": [1, 2, 3] }
+///   ],
+///   "constraints": [
+///     { "all_different": ["A", "B"] },
+///     { "not_equal": ["A", "B"] },
+///     { "linear": { "terms": [[1, "A"], [2, "B"]], "relation": "<=", "constant": 7 } },
+///     { "table": { "variables": ["A", "B"], "tuples": [[1, 2], [2, 3]] } }
+///   ]
+/// }
+/// ```
+///
+/// or in a small text format, with one statement per line and `#` starting a comment:
+///
+/// ```text
+/// var A in 1..3
+/// var B in {1, 2, 3}
+/// all_different A B
+/// A + 2 B <= 7
+/// table A B: (1, 2), (2, 3)
+/// ```
+///
+/// In the text format, any comparison (`=`, `!=`, `<`, `<=`, `>`, `>=`) between two sums of terms is a linear constraint.
+/// A term is an integer, a variable, or a variable times an integer, written `3 X`, `3 * X` or `X * 3`,
+/// and each term may have its own minus sign, as in `A - -1 = B`. Anything else, e.g., a product of two variables,
+/// brackets, or a number too big for an `i64`, is a syntax error on that line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Problem {
+    pub variables: Vec<VariableSpec>,
+    #[serde(default)]
+    pub constraints: Vec<ConstraintSpec>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableSpec {
+    pub name: String,
+    pub domain: DomainSpec,
+}
+
+/// The values of a variable: either listed one by one, or every integer from `min` to `max` inclusive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DomainSpec {
+    Values(Vec<i64>),
+    Range { min: i64, max: i64 },
+}
+
+impl DomainSpec {
+    /// The most values a range may have. Problem files come from anywhere, and a range is turned into a list of values,
+    /// so `0..10000000000` must be an error rather than run out of memory.
+    pub const MAX_RANGE_SIZE: u64 = 1 << 20;
+
+    pub fn values(&self) -> Result<Vec<i64>, ProblemError> {
+        match self {
+            DomainSpec::Values(values) => Ok(values.clone()),
+            DomainSpec::Range { min, max } => {
+                // `i128`, since `max - min` does not fit in an `i64` for the widest ranges.
+                let size = i128::from(*max) - i128::from(*min) + 1;
+
+                if size > i128::from(Self::MAX_RANGE_SIZE) {
+                    return Err(ProblemError::Invalid(format!(
+                        "the range {min}..{max} has more than {} values",
+                        Self::MAX_RANGE_SIZE
+                    )));
+                }
+
+                Ok((*min..=*max).collect())
+            }
+        }
+    }
+}
+
+/// A `StandardConstraint` over variables given by name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstraintSpec {
+    AllDifferent(Vec<String>),
+    NotEqual(String, String),
+    Linear {
+        terms: Vec<(i64, String)>,
+        relation: Relation,
+        constant: i64,
+    },
+    Table {
+        variables: Vec<String>,
+        tuples: Vec<Vec<i64>>,
+    },
+}
+
+impl ConstraintSpec {
+    pub fn to_constraint(&self) -> StandardConstraint<String, i64> {
+        match self {
+            ConstraintSpec::AllDifferent(variables) => {
+                StandardConstraint::all_different(variables.clone())
+            }
+            ConstraintSpec::NotEqual(first, second) => {
+                StandardConstraint::not_equal(first.clone(), second.clone())
+            }
+            ConstraintSpec::Linear {
+                terms,
+                relation,
+                constant,
+            } => StandardConstraint::linear(terms.clone(), *relation, *constant),
+            ConstraintSpec::Table { variables, tuples } => {
+                StandardConstraint::table(variables.clone(), tuples.clone())
+            }
+        }
+    }
+}
+
+/// # Problem Error
+///
+/// Why a problem could not be read, or did not make a valid CSP.
+#[derive(Debug)]
+pub enum ProblemError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// A line of the text format did not parse. `line` counts from 1.
+    Syntax {
+        line: usize,
+        text: String,
+    },
+    Csp(CspError<String>),
+    /// The input parsed, but does not hold together, e.g., a DIMACS header that disagrees with the clauses after it.
+    Invalid(String),
+}
+
+impl fmt::Display for ProblemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemError::Io(error) => write!(f, "could not read problem: {error}"),
+            ProblemError::Json(error) => write!(f, "invalid JSON problem: {error}"),
+            ProblemError::Syntax { line, text } => {
+                write!(f, "syntax error on line {line}: {text}")
+            }
+            ProblemError::Csp(error) => write!(f, "invalid problem: {error}"),
+            ProblemError::Invalid(message) => write!(f, "invalid problem: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ProblemError {}
+
+impl From<io::Error> for ProblemError {
+    fn from(error: io::Error) -> Self {
+        ProblemError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ProblemError {
+    fn from(error: serde_json::Error) -> Self {
+        ProblemError::Json(error)
+    }
+}
+
+impl From<CspError<String>> for ProblemError {
+    fn from(error: CspError<String>) -> Self {
+        ProblemError::Csp(error)
+    }
+}
+
+impl Problem {
+    pub fn from_json(input: &str) -> Result<Self, ProblemError> {
+        Ok(serde_json::from_str(input)?)
+    }
+
+    pub fn from_text(input: &str) -> Result<Self, ProblemError> {
+        let mut problem = Problem {
+            variables: Vec::new(),
+            constraints: Vec::new(),
+        };
+
+        for (index, line) in input.lines().enumerate() {
+            let statement = line.split('#').next().unwrap_or_default().trim();
+
+            if statement.is_empty() {
+                continue;
+            }
+
+            let (_, statement) =
+                all_consuming(parse_statement)(statement).map_err(|_| ProblemError::Syntax {
+                    line: index + 1,
+                    text: line.to_string(),
+                })?;
+
+            match statement {
+                Statement::Variables(names, domain) => {
+                    problem
+                        .variables
+                        .extend(names.into_iter().map(|name| VariableSpec {
+                            name,
+                            domain: domain.clone(),
+                        }));
+                }
+                Statement::Constraint(constraint) => problem.constraints.push(constraint),
+            }
+        }
+
+        Ok(problem)
+    }
+
+    /// Read a problem from a file: JSON if the file name ends in `.json`, the text format otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ProblemError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::from_json(&input)
+        } else {
+            Self::from_text(&input)
+        }
+    }
+
+    /// Build the CSP this problem describes, with the default search settings.
+    pub fn to_csp(&self) -> Result<ProblemCsp, ProblemError> {
+        let domains = self
+            .variables
+            .iter()
+            .map(|variable| Ok((variable.name.clone(), variable.domain.values()?)))
+            .collect::<Result<_, ProblemError>>()?;
+
+        let mut csp = CSP::new(
+            self.variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect(),
+            domains,
+        )?;
+
+        for constraint in &self.constraints {
+            csp.add_constraint(constraint.to_constraint())?;
+        }
+
+        Ok(csp)
+    }
+}
+
+/// One line of the text format.
+enum Statement {
+    Variables(Vec<String>, DomainSpec),
+    Constraint(ConstraintSpec),
+}
+
+pub(super) fn parse_number(input: &str) -> IResult<&str, i64> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// A signed integer, e.g., in a domain or a table.
+pub(super) fn parse_integer(input: &str) -> IResult<&str, i64> {
+    map(pair(opt(char('-')), parse_number), |(sign, number)| {
+        if sign.is_some() {
+            -number
+        } else {
+            number
+        }
+    })(input)
+}
+
+fn parse_identifier(input: &str) -> IResult<&str, String> {
+    map(
+        verify(
+            recognize(pair(
+                alt((alpha1, tag("_"))),
+                many0(alt((alphanumeric1, tag("_")))),
+            )),
+            |name: &str| name != "in",
+        ),
+        String::from,
+    )(input)
+}
+
+fn parse_identifiers(input: &str) -> IResult<&str, Vec<String>> {
+    separated_list1(space1, parse_identifier)(input)
+}
+
+/// A comma-separated list of integers, e.g., `1, 2, 3`.
+fn parse_integers(input: &str) -> IResult<&str, Vec<i64>> {
+    separated_list1(tuple((space0, char(','), space0)), parse_integer)(input)
+}
+
+/// `min..max` or `{a, b, c}`.
+fn parse_domain(input: &str) -> IResult<&str, DomainSpec> {
+    alt((
+        map(
+            separated_pair(
+                parse_integer,
+                tuple((space0, tag(".."), space0)),
+                parse_integer,
+            ),
+            |(min, max)| DomainSpec::Range { min, max },
+        ),
+        map(
+            delimited(
+                pair(char('{'), space0),
+                parse_integers,
+                pair(space0, char('}')),
+            ),
+            DomainSpec::Values,
+        ),
+    ))(input)
+}
+
+/// `var A B C in <domain>`
+fn parse_variables(input: &str) -> IResult<&str, Statement> {
+    map(
+        preceded(
+            pair(tag("var"), space1),
+            separated_pair(
+                parse_identifiers,
+                tuple((space1, tag("in"), space1)),
+                parse_domain,
+            ),
+        ),
+        |(names, domain)| Statement::Variables(names, domain),
+    )(input)
+}
+
+/// `all_different A B C`
+fn parse_all_different(input: &str) -> IResult<&str, ConstraintSpec> {
+    map(
+        preceded(pair(tag("all_different"), space1), parse_identifiers),
+        ConstraintSpec::AllDifferent,
+    )(input)
+}
+
+/// `table A B: (1, 2), (2, 3)`
+fn parse_table(input: &str) -> IResult<&str, ConstraintSpec> {
+    let parse_tuple = delimited(
+        pair(char('('), space0),
+        parse_integers,
+        pair(space0, char(')')),
+    );
+
+    map(
+        preceded(
+            pair(tag("table"), space1),
+            separated_pair(
+                parse_identifiers,
+                tuple((space0, char(':'), space0)),
+                separated_list1(tuple((space0, char(','), space0)), parse_tuple),
+            ),
+        ),
+        |(variables, tuples)| ConstraintSpec::Table { variables, tuples },
+    )(input)
+}
+
+fn parse_relation(input: &str) -> IResult<&str, Relation> {
+    alt((
+        value(Relation::LessOrEqual, tag("<=")),
+        value(Relation::GreaterOrEqual, tag(">=")),
+        value(Relation::NotEqual, tag("!=")),
+        value(Relation::Equal, tag("=")),
+        value(Relation::Less, tag("<")),
+        value(Relation::Greater, tag(">")),
+    ))(input)
+}
+
+/// `3 X`, `3 * X`, `X * 3`, `X` or `3`, as a coefficient and the variable, if there is one.
+fn parse_unsigned_term(input: &str) -> IResult<&str, (i64, Option<String>)> {
+    alt((
+        map(
+            separated_pair(
+                parse_number,
+                tuple((space0, opt(char('*')), space0)),
+                parse_identifier,
+            ),
+            |(coefficient, variable)| (coefficient, Some(variable)),
+        ),
+        map(
+            separated_pair(
+                parse_identifier,
+                tuple((space0, char('*'), space0)),
+                parse_number,
+            ),
+            |(variable, coefficient)| (coefficient, Some(variable)),
+        ),
+        map(parse_identifier, |variable| (1, Some(variable))),
+        map(parse_number, |constant| (constant, None)),
+    ))(input)
+}
+
+/// A term with an optional minus sign of its own, e.g., `-3 X` or `-1`.
+fn parse_term(input: &str) -> IResult<&str, (i64, Option<String>)> {
+    map(
+        pair(opt(terminated(char('-'), space0)), parse_unsigned_term),
+        |(negative, (coefficient, variable))| {
+            if negative.is_some() {
+                (-coefficient, variable)
+            } else {
+                (coefficient, variable)
+            }
+        },
+    )(input)
+}
+
+/// A sum of terms, e.g., `-2 X + Y - 3`.
+fn parse_sum(input: &str) -> IResult<&str, Vec<(i64, Option<String>)>> {
+    let parse_sign = map(one_of("+-"), |sign| if sign == '-' { -1 } else { 1 });
+
+    map(
+        pair(
+            parse_term,
+            many0(pair(delimited(space0, parse_sign, space0), parse_term)),
+        ),
+        |(first, rest)| {
+            std::iter::once(first)
+                .chain(
+                    rest.into_iter()
+                        .map(|(sign, (coefficient, variable))| (sign * coefficient, variable)),
+                )
+                .collect()
+        },
+    )(input)
+}
+
+/// `<sum> <relation> <sum>`, rearranged so the variables are on the left and the constant is on the right.
+/// Fails if adding up the constants overflows.
+fn parse_linear(input: &str) -> IResult<&str, ConstraintSpec> {
+    map_opt(
+        tuple((
+            parse_sum,
+            delimited(space0, parse_relation, space0),
+            parse_sum,
+        )),
+        |(left, relation, right)| {
+            let mut terms = Vec::new();
+            let mut constant: i64 = 0;
+
+            let left = left.into_iter().map(|term| (1, term));
+            let right = right.into_iter().map(|term| (-1, term));
+
+            for (side, (coefficient, variable)) in left.chain(right) {
+                match variable {
+                    Some(variable) => terms.push((side * coefficient, variable)),
+                    None => constant = constant.checked_sub(side * coefficient)?,
+                }
+            }
+
+            Some(ConstraintSpec::Linear {
+                terms,
+                relation,
+                constant,
+            })
+        },
+    )(input)
+}
+
+fn parse_statement(input: &str) -> IResult<&str, Statement> {
+    alt((
+        parse_variables,
+        map(
+            alt((parse_all_different, parse_table, parse_linear)),
+            Statement::Constraint,
+        ),
+    ))(input)
+}
@@ -0,0 +1,292 @@
+use std::collections::{HashMap, VecDeque};
+use std::{fmt, hash::Hash};
+
+use rand::prelude::*;
+
+use super::{Constraint, CSP};
+
+/// # Local Search Strategy
+///
+/// How `CSP::local_search` picks a new value for a conflicted variable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LocalSearchStrategy {
+    /// Move to the value that violates the fewest constraints, breaking ties at random.
+    MinConflicts,
+    /// Min-conflicts, but a variable may not go back to a value it had in the last `tenure` steps
+    /// unless that would beat the best assignment found so far. This stops the search from cycling on a plateau.
+    Tabu { tenure: usize },
+    /// Try a random value, always accepting it if it is no worse, and otherwise with probability `e^(-delta / temperature)`.
+    /// The temperature is multiplied by `cooling_rate` after every step.
+    SimulatedAnnealing {
+        initial_temperature: f64,
+        cooling_rate: f64,
+    },
+}
+
+/// # Local Search Config
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalSearchConfig {
+    pub strategy: LocalSearchStrategy,
+    /// Give up after this many steps.
+    pub max_steps: usize,
+    /// Seed for the random number generator, so that runs can be reproduced.
+    /// `None` seeds it from the operating system.
+    pub seed: Option<u64>,
+}
+
+impl Default for LocalSearchConfig {
+    fn default() -> Self {
+        Self {
+            strategy: LocalSearchStrategy::MinConflicts,
+            max_steps: 10_000,
+            seed: None,
+        }
+    }
+}
+
+/// # Local Search Result
+///
+/// The best assignment a local search found. Every variable is assigned, but unless `conflicts` is zero,
+/// some constraints are still violated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalSearchResult<V, D>
+where
+    V: Hash + Eq,
+{
+    pub assignment: HashMap<V, D>,
+    /// Constraints the assignment violates.
+    pub conflicts: usize,
+    /// Steps taken before finding a solution or running out of steps.
+    pub steps: usize,
+}
+
+impl<V, D> LocalSearchResult<V, D>
+where
+    V: Hash + Eq,
+{
+    pub fn is_solution(&self) -> bool {
+        self.conflicts == 0
+    }
+
+    /// The assignment, if it satisfies every constraint.
+    pub fn solution(self) -> Option<HashMap<V, D>> {
+        if self.is_solution() {
+            Some(self.assignment)
+        } else {
+            None
+        }
+    }
+}
+
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    /// Count the constraints on `variable` that `assignment` violates.
+    fn conflicts_for(&self, variable: &V, assignment: &HashMap<V, D>) -> usize {
+        self.constraints.get(variable).map_or(0, |constraints| {
+            constraints
+                .iter()
+                .filter(|constraint| !constraint.is_satisfied(assignment))
+                .count()
+        })
+    }
+
+    /// Count the constraints that `assignment` violates.
+    fn total_conflicts(&self, assignment: &HashMap<V, D>) -> usize {
+        self.registered_constraints()
+            .filter(|constraint| !constraint.is_satisfied(assignment))
+            .count()
+    }
+
+    /// # Local Search
+    ///
+    /// Rather than building up an assignment one variable at a time like the backtracking search,
+    /// start from a random complete assignment and repeatedly change the value of a variable that violates some constraint.
+    ///
+    /// This cannot prove that a problem has no solution, but it scales to problems far too large for backtracking,
+    /// like placing a thousand queens.
+    pub fn local_search(&self, config: &LocalSearchConfig) -> LocalSearchResult<V, D> {
+        let mut rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let empty = Vec::new();
+        let domains: Vec<&Vec<D>> = self
+            .variables
+            .iter()
+            .map(|variable| self.domains.get(variable).unwrap_or(&empty))
+            .collect();
+
+        // Track values by their index in the domain, so tabu moves can be recorded without requiring `D: PartialEq`.
+        let mut current: Vec<usize> = domains
+            .iter()
+            .map(|domain| rng.gen_range(0..domain.len().max(1)))
+            .collect();
+
+        let mut assignment = HashMap::new();
+
+        for (index, variable) in self.variables.iter().enumerate() {
+            if let Some(value) = domains[index].get(current[index]) {
+                assignment.insert(variable.clone(), value.clone());
+            }
+        }
+
+        let positions: HashMap<&V, usize> = self
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (variable, index))
+            .collect();
+
+        // Violated constraints per variable, kept up to date as variables move,
+        // so picking a conflicted variable does not mean checking every constraint again.
+        let mut variable_conflicts: Vec<usize> = self
+            .variables
+            .iter()
+            .map(|variable| self.conflicts_for(variable, &assignment))
+            .collect();
+
+        let mut conflicts = self.total_conflicts(&assignment);
+        let mut best = LocalSearchResult {
+            assignment: assignment.clone(),
+            conflicts,
+            steps: 0,
+        };
+
+        let mut tabu = VecDeque::<(usize, usize)>::new();
+        let mut temperature = match config.strategy {
+            LocalSearchStrategy::SimulatedAnnealing {
+                initial_temperature,
+                ..
+            } => initial_temperature,
+            _ => 0.0,
+        };
+
+        for step in 1..=config.max_steps {
+            if conflicts == 0 {
+                break;
+            }
+
+            best.steps = step;
+
+            let conflicted: Vec<usize> = (0..self.variables.len())
+                .filter(|&index| !domains[index].is_empty() && variable_conflicts[index] > 0)
+                .collect();
+
+            let Some(&index) = conflicted.choose(&mut rng) else {
+                break;
+            };
+
+            let variable = &self.variables[index];
+            let domain = domains[index];
+            let conflicts_before = variable_conflicts[index];
+
+            let conflicts_with = |value_index: usize, assignment: &mut HashMap<V, D>| {
+                assignment.insert(variable.clone(), domain[value_index].clone());
+
+                let count = self.conflicts_for(variable, assignment);
+
+                assignment.insert(variable.clone(), domain[current[index]].clone());
+
+                count
+            };
+
+            let chosen = match config.strategy {
+                LocalSearchStrategy::MinConflicts | LocalSearchStrategy::Tabu { .. } => {
+                    let mut candidates = Vec::new();
+                    let mut fewest = usize::MAX;
+
+                    for value_index in 0..domain.len() {
+                        let count = conflicts_with(value_index, &mut assignment);
+                        let is_tabu = tabu.contains(&(index, value_index));
+                        let aspires = conflicts + count < best.conflicts + conflicts_before;
+
+                        if is_tabu && !aspires {
+                            continue;
+                        }
+
+                        if count < fewest {
+                            fewest = count;
+                            candidates.clear();
+                        }
+
+                        if count == fewest {
+                            candidates.push((value_index, count));
+                        }
+                    }
+
+                    candidates.choose(&mut rng).copied()
+                }
+                LocalSearchStrategy::SimulatedAnnealing { cooling_rate, .. } => {
+                    let value_index = rng.gen_range(0..domain.len());
+                    let count = conflicts_with(value_index, &mut assignment);
+                    let delta = count as f64 - conflicts_before as f64;
+
+                    temperature *= cooling_rate;
+
+                    if delta <= 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                        Some((value_index, count))
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            let Some((value_index, count)) = chosen else {
+                continue;
+            };
+
+            if let LocalSearchStrategy::Tabu { tenure } = config.strategy {
+                tabu.push_back((index, current[index]));
+
+                while tabu.len() > tenure {
+                    tabu.pop_front();
+                }
+            }
+
+            let constraints = self
+                .constraints
+                .get(variable)
+                .map_or(&[][..], Vec::as_slice);
+            let satisfied_before: Vec<bool> = constraints
+                .iter()
+                .map(|constraint| constraint.is_satisfied(&assignment))
+                .collect();
+
+            current[index] = value_index;
+            assignment.insert(variable.clone(), domain[value_index].clone());
+
+            for (constraint, was_satisfied) in constraints.iter().zip(satisfied_before) {
+                let is_satisfied = constraint.is_satisfied(&assignment);
+
+                if is_satisfied == was_satisfied {
+                    continue;
+                }
+
+                for other in constraint.variables() {
+                    if let Some(&position) = positions.get(&other) {
+                        if is_satisfied {
+                            variable_conflicts[position] -= 1;
+                        } else {
+                            variable_conflicts[position] += 1;
+                        }
+                    }
+                }
+            }
+
+            // Only the constraints on the variable that moved can have changed.
+            conflicts = conflicts + count - conflicts_before;
+
+            if conflicts < best.conflicts {
+                best.assignment = assignment.clone();
+                best.conflicts = conflicts;
+            }
+        }
+
+        best
+    }
+}
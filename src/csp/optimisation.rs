@@ -0,0 +1,130 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::{fmt, hash::Hash};
+
+use super::{Constraint, SearchConfig, StopReason, SymmetryBreaking, CSP};
+
+/// # Objective
+///
+/// A cost to minimise over the solutions of a CSP. To maximise instead, wrap the cost in `std::cmp::Reverse`.
+///
+/// Any closure from an assignment to an `Ord` cost is an objective, but implementing `lower_bound` as well
+/// lets the search skip whole branches that cannot beat the best solution found so far.
+/// For floating-point costs, use `OrderedFloat` like the A* search does.
+pub trait Objective<V, D> {
+    type Cost: Clone + Ord;
+
+    /// The cost of a complete assignment.
+    fn cost(&self, solution: &HashMap<V, D>) -> Self::Cost;
+
+    /// A cost no complete assignment extending `partial` can go below, if one is known.
+    /// An estimate that is too high makes the search miss the optimum, so only return what you can guarantee.
+    fn lower_bound(&self, _partial: &HashMap<V, D>) -> Option<Self::Cost> {
+        None
+    }
+}
+
+impl<V, D, F, Cost> Objective<V, D> for F
+where
+    F: Fn(&HashMap<V, D>) -> Cost,
+    Cost: Clone + Ord,
+{
+    type Cost = Cost;
+
+    fn cost(&self, solution: &HashMap<V, D>) -> Cost {
+        self(solution)
+    }
+}
+
+/// # Optimisation Outcome
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptimisationOutcome<V, D, Cost>
+where
+    V: Hash + Eq,
+{
+    /// The whole search space was explored, so no solution is cheaper than this one.
+    Optimal { solution: HashMap<V, D>, cost: Cost },
+    /// There are no solutions at all.
+    Unsatisfiable,
+    /// The search hit one of its limits first. `best` is the cheapest solution found until then, if any.
+    GaveUp {
+        best: Option<(HashMap<V, D>, Cost)>,
+        reason: StopReason,
+    },
+}
+
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    /// # Minimise
+    ///
+    /// Find the solution with the lowest cost under `objective` by branch and bound:
+    /// enumerate solutions as usual, but once one has been found, prune every branch whose lower bound is no better.
+    ///
+    /// `on_improvement` is called with every solution that beats the previous best, so a long search can report progress,
+    /// and the best solution so far survives hitting a limit in `config`.
+    ///
+    /// Symmetries declared with `add_symmetry` are ignored here: the objective can tell symmetric solutions apart.
+    pub fn minimise<O, F>(
+        &self,
+        objective: &O,
+        config: SearchConfig,
+        mut on_improvement: F,
+    ) -> OptimisationOutcome<V, D, O::Cost>
+    where
+        O: Objective<V, D>,
+        F: FnMut(&HashMap<V, D>, &O::Cost),
+    {
+        // Breaking a symmetry keeps one solution out of each symmetric set, which need not be the cheapest one.
+        let csp = CSP {
+            variables: self.variables.clone(),
+            domains: self.domains.clone(),
+            constraints: self.constraints.clone(),
+            variable_ordering: self.variable_ordering,
+            value_ordering: self.value_ordering,
+            inference: self.inference,
+            backtracking: self.backtracking,
+            domain_representation: self.domain_representation,
+            soft_constraints: Vec::new(),
+            symmetry: SymmetryBreaking::default(),
+        };
+
+        let best_cost = RefCell::new(None::<O::Cost>);
+        let mut best = None;
+
+        let mut solutions = csp.solutions().with_config(config).with_pruning(|partial| {
+            match best_cost.borrow().as_ref() {
+                Some(best_cost) => objective
+                    .lower_bound(partial)
+                    .is_some_and(|bound| bound >= *best_cost),
+                None => false,
+            }
+        });
+
+        for solution in solutions.by_ref() {
+            let cost = objective.cost(&solution);
+
+            if best_cost
+                .borrow()
+                .as_ref()
+                .is_some_and(|best_cost| cost >= *best_cost)
+            {
+                continue;
+            }
+
+            on_improvement(&solution, &cost);
+
+            *best_cost.borrow_mut() = Some(cost.clone());
+            best = Some((solution, cost));
+        }
+
+        match (solutions.stopped(), best) {
+            (Some(reason), best) => OptimisationOutcome::GaveUp { best, reason },
+            (None, Some((solution, cost))) => OptimisationOutcome::Optimal { solution, cost },
+            (None, None) => OptimisationOutcome::Unsatisfiable,
+        }
+    }
+}
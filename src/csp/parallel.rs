@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fmt, hash::Hash, thread};
+
+use super::{CancellationToken, Constraint, Model, SearchConfig, SearchStats, CSP};
+
+/// How many subtrees to aim for per thread, so a thread that gets an easy one can move on to another.
+const BRANCHES_PER_THREAD: usize = 4;
+
+/// Parallel search needs to share the CSP between threads and send solutions back,
+/// so unlike the rest of `CSP`, it requires the variables, values and constraints to be `Send + Sync`.
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord + Send + Sync,
+    D: Clone + fmt::Debug + Send + Sync,
+    C: Clone + Constraint<V, D> + Send + Sync,
+{
+    /// The assignments that extend `assignment` by one value for the next variable to assign,
+    /// leaving out values that violate a constraint or break a symmetry straight away.
+    ///
+    /// Returns `None` if there is no variable left to assign.
+    fn branches(
+        &self,
+        model: &Model<'_, V, D, C>,
+        assignment: &HashMap<V, D>,
+    ) -> Option<Vec<HashMap<V, D>>> {
+        let mut stats = SearchStats::default();
+        let mut state = model.state(assignment.clone());
+
+        if !model.infer_initial(&mut state, &mut stats) {
+            return Some(Vec::new());
+        }
+
+        let variable = model.select_unassigned_variable(&mut state, &mut stats)?;
+        let name = &self.variables[variable];
+
+        let branches = model
+            .value_order(variable, &mut state, &mut stats)
+            .into_iter()
+            .filter_map(|index| {
+                let mut branch = assignment.clone();
+
+                branch.insert(name.clone(), model.domains[variable][index].clone());
+
+                (model
+                    .violated_constraint(variable, &branch, &mut stats)
+                    .is_none()
+                    && model.allowed_by_symmetries(&branch))
+                .then_some(branch)
+            })
+            .collect();
+
+        Some(branches)
+    }
+
+    /// # Split
+    ///
+    /// Divide the search tree into at least `target` subtrees, if it has that many, each given by a partial assignment
+    /// that can be searched on its own. The subtrees are in the order the sequential search would visit them.
+    fn split(&self, target: usize) -> Vec<HashMap<V, D>> {
+        let model = Model::new(self);
+        let mut frontier = vec![HashMap::new()];
+
+        loop {
+            if frontier.len() >= target {
+                return frontier;
+            }
+
+            let mut next = Vec::new();
+            let mut expanded = false;
+
+            for assignment in frontier {
+                match self.branches(&model, &assignment) {
+                    Some(branches) => {
+                        next.extend(branches);
+                        expanded = true;
+                    }
+                    None => next.push(assignment),
+                }
+            }
+
+            frontier = next;
+
+            if !expanded {
+                return frontier;
+            }
+        }
+    }
+
+    /// Search the subtrees from `split` on `threads` threads, each one taking the next unsearched subtree when it is done.
+    fn search_in_parallel<T, F>(&self, threads: usize, search: F) -> Vec<(usize, T)>
+    where
+        T: Send,
+        F: Fn(HashMap<V, D>) -> Option<T> + Sync,
+    {
+        let threads = threads.max(1);
+        let branches = self.split(threads * BRANCHES_PER_THREAD);
+        let next_branch = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+
+                        loop {
+                            let index = next_branch.fetch_add(1, Ordering::Relaxed);
+
+                            let Some(branch) = branches.get(index) else {
+                                break;
+                            };
+
+                            if let Some(result) = search(branch.clone()) {
+                                results.push((index, result));
+                            }
+                        }
+
+                        results
+                    })
+                })
+                .collect();
+
+            let mut results: Vec<(usize, T)> = workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("search thread panicked"))
+                .collect();
+
+            results.sort_by_key(|&(index, _)| index);
+            results
+        })
+    }
+
+    /// # Parallel Backtracking Search
+    ///
+    /// Find a solution using `threads` threads, each searching its own part of the search tree.
+    /// The first thread to find a solution stops the others, so which solution comes back can differ from run to run.
+    ///
+    /// `std::thread::available_parallelism` is a good choice for `threads`.
+    pub fn parallel_backtracking_search(&self, threads: usize) -> Option<HashMap<V, D>> {
+        let found = CancellationToken::new();
+
+        let results = self.search_in_parallel(threads, |branch| {
+            if found.is_cancelled() {
+                return None;
+            }
+
+            let solution = self
+                .solutions_with_assignment(branch)
+                .with_config(SearchConfig::new().with_cancellation(found.clone()))
+                .next();
+
+            if solution.is_some() {
+                found.cancel();
+            }
+
+            solution
+        });
+
+        results.into_iter().next().map(|(_, solution)| solution)
+    }
+
+    /// Find every solution using `threads` threads. See `parallel_backtracking_search`.
+    ///
+    /// The solutions of each part of the search tree are kept together, in the order the sequential search would visit those parts,
+    /// so the result does not depend on how the threads happened to be scheduled.
+    pub fn parallel_solutions(&self, threads: usize) -> Vec<HashMap<V, D>> {
+        self.search_in_parallel(threads, |branch| {
+            Some(self.solutions_with_assignment(branch).collect::<Vec<_>>())
+        })
+        .into_iter()
+        .flat_map(|(_, solutions)| solutions)
+        .collect()
+    }
+
+    /// Count every solution using `threads` threads, without keeping them around.
+    pub fn parallel_count_solutions(&self, threads: usize) -> usize {
+        self.search_in_parallel(threads, |branch| {
+            Some(self.solutions_with_assignment(branch).count())
+        })
+        .into_iter()
+        .map(|(_, count)| count)
+        .sum()
+    }
+}
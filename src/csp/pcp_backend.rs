@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::{fmt, hash::Hash};
+
+use gcollections::ops::*;
+use interval::interval_set::*;
+use pcp::concept::*;
+use pcp::kernel::*;
+use pcp::logic::{Conjunction, Disjunction};
+use pcp::propagators::*;
+use pcp::search::search_tree_visitor::Status::*;
+use pcp::search::*;
+use pcp::term::*;
+use pcp::variable::ops::*;
+
+use super::domain::runs;
+use super::{Constraint, Relation, StandardConstraint, CSP};
+
+/// # libpcp Error
+///
+/// Why a CSP could not be handed over to libpcp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PcpError<V> {
+    /// A predicate or custom constraint over these variables. libpcp cannot run arbitrary Rust code as a constraint.
+    UnsupportedConstraint(Vec<V>),
+    /// libpcp works with `i32`, and a value, constant or coefficient times a value involving these variables does not fit.
+    OutOfRange(Vec<V>),
+}
+
+impl<V> fmt::Display for PcpError<V>
+where
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PcpError::UnsupportedConstraint(variables) => write!(
+                f,
+                "the constraint on {variables:?} is not one libpcp supports"
+            ),
+            PcpError::OutOfRange(variables) => write!(
+                f,
+                "the constraint or domain of {variables:?} needs integers that do not fit in an i32"
+            ),
+        }
+    }
+}
+
+impl<V> std::error::Error for PcpError<V> where V: fmt::Debug {}
+
+/// libpcp's view of an integer variable, or of an expression over them.
+type PcpVar = Var<VStore>;
+
+impl<V, D> CSP<V, D, StandardConstraint<V, D>>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Copy + fmt::Debug + PartialEq + Into<i64>,
+{
+    /// # Compile to libpcp
+    ///
+    /// Build a libpcp `FDSpace` for this CSP, so it can be solved by propagation instead of our backtracking search.
+    ///
+    /// The first variables of the space are the ones in `self.variables`, in the same order,
+    /// each with its domain as a set of intervals.
+    /// Any variables after those hold `coefficient * variable` for linear constraints.
+    ///
+    /// Every standard constraint over integer values translates directly, except predicates and custom constraints.
+    pub fn to_pcp(&self) -> Result<FDSpace, PcpError<V>> {
+        let mut space = FDSpace::empty();
+        let mut variables: HashMap<&V, PcpVar> = HashMap::new();
+
+        for variable in &self.variables {
+            let domain = self
+                .integers(variable, 1)
+                .ok_or_else(|| PcpError::OutOfRange(vec![variable.clone()]))?;
+
+            variables.insert(variable, Box::new(space.vstore.alloc(domain)));
+        }
+
+        for constraint in self.registered_constraints() {
+            for formula in self.compile(constraint, &mut space.vstore, &variables)? {
+                space.cstore.alloc(formula);
+            }
+        }
+
+        Ok(space)
+    }
+
+    /// # libpcp Search
+    ///
+    /// Solve this CSP with libpcp rather than `backtracking_search`.
+    /// The solution is a valid one, but not necessarily the one the backtracking search would find first.
+    pub fn pcp_search(&self) -> Result<Option<HashMap<V, D>>, PcpError<V>> {
+        let space = self.to_pcp()?;
+
+        let mut search = one_solution_engine();
+        search.start(&space);
+        let (frozen_space, status) = search.enter(space);
+        let space = frozen_space.unfreeze();
+
+        match status {
+            Satisfiable => Ok(Some(
+                self.variables
+                    .iter()
+                    .zip(space.vstore.iter())
+                    .map(|(variable, domain)| {
+                        // Every variable is assigned by now, so its domain is a single integer.
+                        let integer = i64::from(domain.lower());
+
+                        let value = self.domains[variable]
+                            .iter()
+                            .copied()
+                            .find(|&value| value.into() == integer)
+                            .expect("libpcp only assigns values from the domain");
+
+                        (variable.clone(), value)
+                    })
+                    .collect(),
+            )),
+            Unsatisfiable | EndOfSearch => Ok(None),
+            Unknown(_) => unreachable!(
+                "After the search step, the problem instance should be either satisfiable or unsatisfiable."
+            ),
+        }
+    }
+
+    /// The values of `variable`, each multiplied by `coefficient`, as an interval set of `i32`.
+    fn integers(&self, variable: &V, coefficient: i64) -> Option<IntervalSet<i32>> {
+        let values: Vec<i64> = self.domains[variable]
+            .iter()
+            .map(|&value| value.into().checked_mul(coefficient))
+            .collect::<Option<_>>()?;
+
+        let runs: Vec<(i32, i32)> = runs(&values)
+            .into_iter()
+            .map(|(lower, upper)| Some((bound(lower)?, bound(upper)?)))
+            .collect::<Option<_>>()?;
+
+        Some(runs.to_interval_set())
+    }
+
+    /// Translate one constraint into libpcp propagators.
+    /// New variables for the terms of a linear constraint are allocated in `vstore`.
+    fn compile(
+        &self,
+        constraint: &StandardConstraint<V, D>,
+        vstore: &mut VStore,
+        variables: &HashMap<&V, PcpVar>,
+    ) -> Result<Vec<Formula<VStore>>, PcpError<V>> {
+        let var = |variable: &V| variables[variable].bclone();
+        let out_of_range = || PcpError::OutOfRange(constraint.variables());
+
+        match constraint {
+            StandardConstraint::AllDifferent(scope) => {
+                let mut formulas: Vec<Formula<VStore>> = Vec::new();
+
+                for (i, first) in scope.iter().enumerate() {
+                    for second in &scope[i + 1..] {
+                        formulas.push(Box::new(XNeqY::new(var(first), var(second))));
+                    }
+                }
+
+                Ok(formulas)
+            }
+            StandardConstraint::NotEqual(first, second) => {
+                Ok(vec![Box::new(XNeqY::new(var(first), var(second)))])
+            }
+            StandardConstraint::Linear {
+                terms,
+                relation,
+                constant,
+                ..
+            } => {
+                let mut formulas: Vec<Formula<VStore>> = Vec::new();
+                // Positive terms on the left, negative ones moved over to the right, so only positive multiples are needed.
+                let mut left: Vec<PcpVar> = Vec::new();
+                let mut right: Vec<PcpVar> = Vec::new();
+
+                for &(coefficient, ref variable) in terms {
+                    let term = match coefficient.abs() {
+                        0 => continue,
+                        1 => var(variable),
+                        multiple => {
+                            let domain =
+                                self.integers(variable, multiple).ok_or_else(out_of_range)?;
+                            let product: PcpVar = Box::new(vstore.alloc(domain));
+                            let multiple = bound(multiple).ok_or_else(out_of_range)?;
+
+                            formulas.push(Box::new(XEqYMulZ::new(
+                                product.bclone(),
+                                var(variable),
+                                Box::new(Constant::new(multiple)),
+                            )));
+
+                            product
+                        }
+                    };
+
+                    if coefficient > 0 {
+                        left.push(term);
+                    } else {
+                        right.push(term);
+                    }
+                }
+
+                // Over integers, `x <= c` is `x < c + 1` and `x >= c` is `x > c - 1`.
+                let constant = match relation {
+                    Relation::LessOrEqual => constant.checked_add(1),
+                    Relation::GreaterOrEqual => constant.checked_sub(1),
+                    _ => Some(*constant),
+                };
+
+                let constant = constant.and_then(bound).ok_or_else(out_of_range)?;
+
+                right.push(Box::new(Constant::new(constant)));
+
+                if left.is_empty() {
+                    left.push(Box::new(Constant::new(0)));
+                }
+
+                let left: PcpVar = Box::new(Sum::new(left));
+                let right: PcpVar = Box::new(Sum::new(right));
+
+                formulas.push(match relation {
+                    Relation::Equal => Box::new(XEqY::new(left, right)),
+                    Relation::NotEqual => Box::new(XNeqY::new(left, right)),
+                    Relation::Less | Relation::LessOrEqual => Box::new(XLessY::new(left, right)),
+                    Relation::Greater | Relation::GreaterOrEqual => {
+                        Box::new(XLessY::new(right, left))
+                    }
+                });
+
+                Ok(formulas)
+            }
+            StandardConstraint::Table { variables, tuples } => {
+                // No tuples at all means nothing is allowed; `0 != 0` is a constraint that always fails.
+                if tuples.is_empty() {
+                    return Ok(vec![Box::new(XNeqY::new(
+                        Box::new(Constant::new(0)),
+                        Box::new(Constant::new(0)),
+                    ))]);
+                }
+
+                let mut allowed: Vec<Formula<VStore>> = Vec::new();
+
+                for tuple in tuples {
+                    let mut equalities: Vec<Formula<VStore>> = Vec::new();
+
+                    for (variable, &value) in variables.iter().zip(tuple) {
+                        let value = bound(value.into()).ok_or_else(out_of_range)?;
+
+                        equalities.push(Box::new(XEqY::new(
+                            var(variable),
+                            Box::new(Constant::new(value)),
+                        )));
+                    }
+
+                    allowed.push(Box::new(Conjunction::new(equalities)));
+                }
+
+                Ok(vec![Box::new(Disjunction::new(allowed))])
+            }
+            StandardConstraint::Predicate { .. } | StandardConstraint::Custom(_) => {
+                Err(PcpError::UnsupportedConstraint(constraint.variables()))
+            }
+        }
+    }
+}
+
+fn bound(integer: i64) -> Option<i32> {
+    i32::try_from(integer).ok()
+}
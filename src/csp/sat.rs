@@ -0,0 +1,672 @@
+use std::collections::HashMap;
+use std::{fmt, fs, hash::Hash, path::Path};
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::space1,
+    combinator::*,
+    multi::separated_list1,
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use super::format::{parse_integer, parse_number};
+use super::{
+    Constraint, CspError, ProblemError, SearchConfig, SearchOutcome, SearchStats, StopReason, CSP,
+};
+
+/// A CSP read from a DIMACS CNF file: variables are numbered from 1, as in the file.
+pub type SatCsp = CSP<u32, bool, Clause<u32>>;
+
+/// # Clause
+///
+/// A disjunction of literals: at least one of the variables has the value it is paired with.
+/// `Clause::new(vec![(1, true), (3, false)])` is `x1 ∨ ¬x3`.
+///
+/// Like the other constraints, a clause is satisfied until every one of its variables is assigned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clause<V> {
+    pub literals: Vec<(V, bool)>,
+}
+
+impl<V> Clause<V> {
+    pub fn new(literals: Vec<(V, bool)>) -> Self {
+        Self { literals }
+    }
+}
+
+impl<V> Constraint<V, bool> for Clause<V>
+where
+    V: Clone + Hash + Eq,
+{
+    fn variables(&self) -> Vec<V> {
+        self.literals
+            .iter()
+            .map(|(variable, _)| variable.clone())
+            .collect()
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<V, bool>) -> bool {
+        self.literals
+            .iter()
+            .any(|(variable, value)| assignment.get(variable).is_none_or(|other| other == value))
+    }
+}
+
+/// # CNF
+///
+/// A boolean formula in conjunctive normal form, as found in SAT benchmarks, e.g., those of SATLIB.
+/// Each clause is a list of non-zero literals: `3` means variable 3 is true, `-3` that it is false.
+///
+/// DIMACS files look like this, with comments starting with `c` and every clause ending in `0`:
+///
+/// ```text
+/// c x1 or not x3, and x2 or x3 or not x1
+/// p cnf 3 2
+/// 1 -3 0
+/// 2 3 -1 0
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cnf {
+    pub variables: u32,
+    pub clauses: Vec<Vec<i64>>,
+}
+
+impl Cnf {
+    pub fn from_dimacs(input: &str) -> Result<Self, ProblemError> {
+        let mut header: Option<(u32, usize)> = None;
+        let mut clauses: Vec<Vec<i64>> = Vec::new();
+        let mut clause: Vec<i64> = Vec::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let syntax_error = || ProblemError::Syntax {
+                line: index + 1,
+                text: line.to_string(),
+            };
+
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+
+            // Some SATLIB files end with a line holding `%`, followed by junk.
+            if line.starts_with('%') {
+                break;
+            }
+
+            if line.starts_with('p') {
+                if header.is_some() {
+                    return Err(syntax_error());
+                }
+
+                let (_, counts) = all_consuming(parse_header)(line).map_err(|_| syntax_error())?;
+
+                header = Some(counts);
+                continue;
+            }
+
+            let Some((variables, _)) = header else {
+                return Err(ProblemError::Invalid(format!(
+                    "line {} comes before the `p cnf` header",
+                    index + 1
+                )));
+            };
+
+            let (_, literals) = all_consuming(separated_list1(space1, parse_integer))(line)
+                .map_err(|_| syntax_error())?;
+
+            for literal in literals {
+                if literal == 0 {
+                    clauses.push(std::mem::take(&mut clause));
+                } else if literal.unsigned_abs() > u64::from(variables) {
+                    return Err(ProblemError::Invalid(format!(
+                        "literal {literal} on line {} is beyond the {variables} variables in the header",
+                        index + 1
+                    )));
+                } else {
+                    clause.push(literal);
+                }
+            }
+        }
+
+        if !clause.is_empty() {
+            return Err(ProblemError::Invalid(
+                "the last clause does not end with `0`".to_string(),
+            ));
+        }
+
+        // The number of clauses in the header is not checked, since plenty of files in the wild get it wrong.
+        let Some((variables, _)) = header else {
+            return Err(ProblemError::Invalid(
+                "there is no `p cnf` header".to_string(),
+            ));
+        };
+
+        Ok(Self { variables, clauses })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ProblemError> {
+        Self::from_dimacs(&fs::read_to_string(path)?)
+    }
+
+    /// # To CSP
+    ///
+    /// Build a CSP with a variable for each of `1..=self.variables`, each either `false` or `true`, and a `Clause` for each clause.
+    ///
+    /// A literal repeated within a clause only counts once, and a clause with both a literal and its negation always holds, so it is left out.
+    /// An empty clause can never hold, but a constraint needs a variable, so it becomes a pair of clauses `x1` and `¬x1` instead,
+    /// which every search then reports as unsatisfiable. The CSP has a variable 1 for them even if the formula has no variables.
+    pub fn to_csp(&self) -> Result<SatCsp, ProblemError> {
+        let has_empty_clause = self.clauses.iter().any(Vec::is_empty);
+        let count = if has_empty_clause {
+            self.variables.max(1)
+        } else {
+            self.variables
+        };
+        let variables: Vec<u32> = (1..=count).collect();
+        let domains = variables
+            .iter()
+            .map(|&variable| (variable, vec![false, true]))
+            .collect();
+
+        // Problem files name their variables, so `ProblemError` only carries errors about named ones.
+        let invalid = |error: CspError<u32>| ProblemError::Invalid(error.to_string());
+
+        let mut csp = CSP::new(variables, domains).map_err(invalid)?;
+
+        if has_empty_clause {
+            for value in [true, false] {
+                csp.add_constraint(Clause::new(vec![(1, value)]))
+                    .map_err(invalid)?;
+            }
+        }
+
+        for clause in self.clauses.iter().filter(|clause| !clause.is_empty()) {
+            let mut literals: Vec<(u32, bool)> = Vec::new();
+            let mut tautology = false;
+
+            for &literal in clause {
+                let variable = literal.unsigned_abs() as u32;
+                let value = literal > 0;
+
+                match literals.iter().find(|(other, _)| *other == variable) {
+                    Some(&(_, other)) => tautology |= other != value,
+                    None => literals.push((variable, value)),
+                }
+            }
+
+            if !tautology {
+                csp.add_constraint(Clause::new(literals)).map_err(invalid)?;
+            }
+        }
+
+        Ok(csp)
+    }
+
+    /// # Solution To DIMACS
+    ///
+    /// Write `solution` the way SAT solvers do: `v` lines listing every variable as a literal that holds, ending with `0`.
+    /// Variables missing from `solution` are left out.
+    pub fn solution_to_dimacs(&self, solution: &HashMap<u32, bool>) -> String {
+        let mut lines = Vec::new();
+        let mut line = String::from("v");
+
+        let literals = (1..=self.variables)
+            .filter_map(|variable| {
+                solution.get(&variable).map(|&value| {
+                    if value {
+                        i64::from(variable)
+                    } else {
+                        -i64::from(variable)
+                    }
+                })
+            })
+            .chain([0]);
+
+        for literal in literals {
+            let literal = literal.to_string();
+
+            // Keep lines short, as some tools expect.
+            if line.len() + 1 + literal.len() > 80 {
+                lines.push(std::mem::replace(&mut line, String::from("v")));
+            }
+
+            line.push(' ');
+            line.push_str(&literal);
+        }
+
+        lines.push(line);
+        lines.join("\n")
+    }
+}
+
+/// `p cnf <variables> <clauses>`
+fn parse_header(input: &str) -> IResult<&str, (u32, usize)> {
+    preceded(
+        tuple((tag("p"), space1, tag("cnf"), space1)),
+        tuple((
+            map_res(parse_number, u32::try_from),
+            preceded(space1, map_res(parse_number, usize::try_from)),
+        )),
+    )(input)
+}
+
+/// A literal of the solver: the index of its variable times two, plus one if it says the variable is true.
+/// Its negation is then just the other value of the lowest bit.
+type Literal = usize;
+
+fn literal(variable: usize, value: bool) -> Literal {
+    variable * 2 + usize::from(value)
+}
+
+fn negate(literal: Literal) -> Literal {
+    literal ^ 1
+}
+
+fn variable_of(literal: Literal) -> usize {
+    literal / 2
+}
+
+/// The value `literal` says its variable has.
+fn value_of(literal: Literal) -> bool {
+    literal % 2 == 1
+}
+
+/// How many conflicts to allow before the first restart; each restart allows half as many again as the last.
+const FIRST_RESTART: u64 = 100;
+
+/// How quickly the activity of variables that stop turning up in conflicts fades.
+const ACTIVITY_DECAY: f64 = 0.95;
+
+/// # CDCL Solver
+///
+/// Conflict-driven clause learning, the way modern SAT solvers work, by variable index like the rest of the search engine.
+///
+/// Like DPLL, it assigns a variable, then propagates units: a clause with every literal false but one must make that one true.
+/// When a clause ends up with every literal false, it works out which decisions led there, learns a clause that rules
+/// that combination out, and jumps back to the latest decision that clause depends on rather than just the previous one.
+///
+/// Each clause watches two of its literals, and is only looked at when one of those becomes false,
+/// so propagation does not touch the clauses that cannot have become units.
+struct Solver {
+    clauses: Vec<Vec<Literal>>,
+    /// For each literal, the clauses watching it.
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    /// For each assigned variable, how many decisions were on the trail when it was assigned.
+    levels: Vec<usize>,
+    /// For each variable assigned by propagation, the clause that forced it.
+    reasons: Vec<Option<usize>>,
+    /// Every literal made true, in order.
+    trail: Vec<Literal>,
+    /// Where each decision starts on the trail.
+    decisions: Vec<usize>,
+    /// How much of the trail has been propagated.
+    propagated: usize,
+    activity: Vec<f64>,
+    bump: f64,
+    /// The value each variable had last, which is tried first when it is next decided.
+    phases: Vec<bool>,
+    stats: SearchStats,
+}
+
+impl Solver {
+    fn new(variables: usize) -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); variables * 2],
+            values: vec![None; variables],
+            levels: vec![0; variables],
+            reasons: vec![None; variables],
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            propagated: 0,
+            activity: vec![0.0; variables],
+            bump: 1.0,
+            phases: vec![false; variables],
+            stats: SearchStats::default(),
+        }
+    }
+
+    fn value(&self, literal: Literal) -> Option<bool> {
+        self.values[variable_of(literal)].map(|value| value == value_of(literal))
+    }
+
+    /// Make `literal` true, because of `reason` if it was forced. Returns `false` if it is already false.
+    fn enqueue(&mut self, literal: Literal, reason: Option<usize>) -> bool {
+        match self.value(literal) {
+            Some(value) => value,
+            None => {
+                let variable = variable_of(literal);
+
+                self.values[variable] = Some(value_of(literal));
+                self.levels[variable] = self.decisions.len();
+                self.reasons[variable] = reason;
+                self.trail.push(literal);
+                self.stats.max_depth = self.stats.max_depth.max(self.trail.len());
+
+                true
+            }
+        }
+    }
+
+    /// Add a clause, watching its first two literals. Returns `false` if it is already violated.
+    /// A unit clause has nothing to watch, so its literal is simply made true.
+    fn add_clause(&mut self, literals: Vec<Literal>) -> bool {
+        match literals.as_slice() {
+            [] => false,
+            &[only] => self.enqueue(only, None),
+            &[first, second, ..] => {
+                let index = self.clauses.len();
+
+                self.watches[first].push(index);
+                self.watches[second].push(index);
+                self.clauses.push(literals);
+
+                true
+            }
+        }
+    }
+
+    /// # Unit Propagation
+    ///
+    /// Make true every literal that is the last one left in a clause, until nothing changes.
+    /// Returns the clause that ended up with every literal false, if any.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = negate(self.trail[self.propagated]);
+            self.propagated += 1;
+
+            let watching = std::mem::take(&mut self.watches[falsified]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+
+            for (position, &clause) in watching.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watching[position..]);
+                    break;
+                }
+
+                self.stats.constraint_checks += 1;
+
+                // Keep the falsified literal second, so the first is the other watched one.
+                if self.clauses[clause][0] == falsified {
+                    self.clauses[clause].swap(0, 1);
+                }
+
+                let other = self.clauses[clause][0];
+
+                if self.value(other) == Some(true) {
+                    kept.push(clause);
+                    continue;
+                }
+
+                let replacement = (2..self.clauses[clause].len())
+                    .find(|&index| self.value(self.clauses[clause][index]) != Some(false));
+
+                if let Some(index) = replacement {
+                    self.clauses[clause].swap(1, index);
+                    self.watches[self.clauses[clause][1]].push(clause);
+                    continue;
+                }
+
+                kept.push(clause);
+
+                if !self.enqueue(other, Some(clause)) {
+                    conflict = Some(clause);
+                }
+            }
+
+            self.watches[falsified].extend(kept);
+
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+
+        None
+    }
+
+    fn bump_activity(&mut self, variable: usize) {
+        self.activity[variable] += self.bump;
+
+        // Rescale before anything overflows; only the order of the activities matters.
+        if self.activity[variable] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+
+            self.bump *= 1e-100;
+        }
+    }
+
+    /// # Conflict Analysis
+    ///
+    /// Work back along the trail from `conflict`, replacing literals assigned since the last decision with the clauses that forced them,
+    /// until only one is left: the first unique implication point. The learnt clause is its negation along with the earlier literals involved.
+    ///
+    /// Returns the learnt clause, with the literal that becomes true after backjumping first and the latest of the rest second,
+    /// along with the decision level to jump back to.
+    fn analyse(&mut self, conflict: usize) -> (Vec<Literal>, usize) {
+        let level = self.decisions.len();
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt: Vec<Literal> = vec![0];
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut position = self.trail.len();
+
+        let uip = loop {
+            for index in 0..self.clauses[clause].len() {
+                let literal = self.clauses[clause][index];
+                let variable = variable_of(literal);
+
+                // The literal a reason clause forced is true, and is the one being replaced.
+                if seen[variable] || self.levels[variable] == 0 || self.value(literal) == Some(true)
+                {
+                    continue;
+                }
+
+                seen[variable] = true;
+                self.bump_activity(variable);
+
+                if self.levels[variable] == level {
+                    pending += 1;
+                } else {
+                    learnt.push(literal);
+                }
+            }
+
+            // The latest literal on the trail that is part of the conflict.
+            let literal = loop {
+                position -= 1;
+
+                if seen[variable_of(self.trail[position])] {
+                    break self.trail[position];
+                }
+            };
+
+            pending -= 1;
+
+            if pending == 0 {
+                break literal;
+            }
+
+            clause = self.reasons[variable_of(literal)]
+                .expect("only decisions have no reason, and there is one decision per level");
+        };
+
+        learnt[0] = negate(uip);
+
+        // Watch the literal from the latest level after the first, so it is the one that becomes false last.
+        let latest = (1..learnt.len()).max_by_key(|&index| self.levels[variable_of(learnt[index])]);
+
+        let backjump = match latest {
+            Some(index) => {
+                learnt.swap(1, index);
+                self.levels[variable_of(learnt[1])]
+            }
+            None => 0,
+        };
+
+        self.bump /= ACTIVITY_DECAY;
+
+        (learnt, backjump)
+    }
+
+    /// Undo every assignment made after the first `level` decisions.
+    fn backjump(&mut self, level: usize) {
+        let Some(&start) = self.decisions.get(level) else {
+            return;
+        };
+
+        for literal in self.trail.drain(start..) {
+            let variable = variable_of(literal);
+
+            self.phases[variable] = value_of(literal);
+            self.values[variable] = None;
+            self.reasons[variable] = None;
+        }
+
+        self.decisions.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    /// The unassigned variable that has turned up in conflicts most, lately.
+    fn pick_variable(&self) -> Option<usize> {
+        (0..self.values.len())
+            .filter(|&variable| self.values[variable].is_none())
+            .max_by(|&first, &second| {
+                self.activity[first]
+                    .total_cmp(&self.activity[second])
+                    // Prefer the earlier variable when it is a tie.
+                    .then(second.cmp(&first))
+            })
+    }
+
+    /// Search until every variable is assigned, returning `false` if there is no way to do that.
+    fn solve(&mut self, config: &SearchConfig) -> Result<bool, StopReason> {
+        let mut conflicts_until_restart = FIRST_RESTART;
+        let mut restart_interval = FIRST_RESTART;
+
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.backtracks += 1;
+
+                if self.decisions.is_empty() {
+                    return Ok(false);
+                }
+
+                let (learnt, level) = self.analyse(conflict);
+                let asserted = learnt[0];
+
+                self.backjump(level);
+
+                let reason = (learnt.len() > 1).then_some(self.clauses.len());
+
+                self.add_clause(learnt);
+                self.enqueue(asserted, reason);
+
+                conflicts_until_restart = conflicts_until_restart.saturating_sub(1);
+
+                continue;
+            }
+
+            if conflicts_until_restart == 0 {
+                // Start over with what has been learnt, so the activities can steer the search somewhere more promising.
+                restart_interval += restart_interval / 2;
+                conflicts_until_restart = restart_interval;
+                self.backjump(0);
+            }
+
+            let Some(variable) = self.pick_variable() else {
+                return Ok(true);
+            };
+
+            if let Some(reason) = config.limit_reached(&self.stats) {
+                return Err(reason);
+            }
+
+            self.stats.nodes += 1;
+            self.decisions.push(self.trail.len());
+            self.enqueue(literal(variable, self.phases[variable]), None);
+        }
+    }
+}
+
+impl<V> CSP<V, bool, Clause<V>>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+{
+    /// # CDCL Search
+    ///
+    /// Solve this boolean CSP as a SAT problem, with conflict-driven clause learning rather than `backtracking_search`.
+    /// See `Solver`. This is usually far faster on formulas with thousands of variables, e.g., SAT benchmarks.
+    ///
+    /// The variable ordering, inference and backtracking settings do not apply; it always propagates units and learns clauses.
+    pub fn cdcl_search(&self) -> Option<HashMap<V, bool>> {
+        self.cdcl_search_with_stats().0
+    }
+
+    /// Solve with CDCL, along with statistics on how much searching it took.
+    /// `nodes` counts decisions, `backtracks` conflicts, and `constraint_checks` visits to a watched clause.
+    pub fn cdcl_search_with_stats(&self) -> (Option<HashMap<V, bool>>, SearchStats) {
+        let (outcome, stats) = self.cdcl(SearchConfig::default());
+
+        match outcome {
+            SearchOutcome::Solved(solution) => (Some(solution), stats),
+            _ => (None, stats),
+        }
+    }
+
+    /// Solve with CDCL, giving up once any limit in `config` is reached.
+    pub fn cdcl_search_with_config(&self, config: SearchConfig) -> SearchOutcome<V, bool> {
+        self.cdcl(config).0
+    }
+
+    fn cdcl(&self, config: SearchConfig) -> (SearchOutcome<V, bool>, SearchStats) {
+        let positions: HashMap<&V, usize> = self
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (variable, index))
+            .collect();
+
+        let mut solver = Solver::new(self.variables.len());
+        let mut consistent = true;
+
+        // A variable whose domain has a single value is as good as a unit clause.
+        for (index, variable) in self.variables.iter().enumerate() {
+            if let [value] = self.domains[variable].as_slice() {
+                consistent &= solver.add_clause(vec![literal(index, *value)]);
+            }
+        }
+
+        for clause in self.registered_constraints() {
+            let literals = clause
+                .literals
+                .iter()
+                .map(|(variable, value)| literal(positions[variable], *value))
+                .collect();
+
+            consistent &= solver.add_clause(literals);
+        }
+
+        if !consistent {
+            return (SearchOutcome::Unsatisfiable, solver.stats);
+        }
+
+        let outcome = match solver.solve(&config) {
+            Ok(true) => SearchOutcome::Solved(
+                self.variables
+                    .iter()
+                    .zip(&solver.values)
+                    .map(|(variable, value)| (variable.clone(), value.unwrap_or_default()))
+                    .collect(),
+            ),
+            Ok(false) => SearchOutcome::Unsatisfiable,
+            Err(reason) => SearchOutcome::GaveUp(reason),
+        };
+
+        (outcome, solver.stats)
+    }
+}
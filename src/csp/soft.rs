@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::{fmt, hash::Hash};
+
+use super::{Constraint, CspError, Objective, OptimisationOutcome, SearchConfig, CSP};
+
+/// # Soft Constraint
+///
+/// A constraint that may be violated at a cost of `weight`, e.g., "Alice would rather not meet before 10".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SoftConstraint<C> {
+    pub constraint: C,
+    pub weight: u64,
+}
+
+/// The total weight of the soft constraints an assignment violates.
+/// Constraints are satisfied until they can tell otherwise, so on a partial assignment this never overestimates the final penalty.
+struct Penalty<'a, C> {
+    soft_constraints: &'a [SoftConstraint<C>],
+}
+
+impl<V, D, C> Objective<V, D> for Penalty<'_, C>
+where
+    C: Constraint<V, D>,
+{
+    type Cost = u64;
+
+    fn cost(&self, solution: &HashMap<V, D>) -> u64 {
+        self.soft_constraints
+            .iter()
+            .filter(|soft| !soft.constraint.is_satisfied(solution))
+            .map(|soft| soft.weight)
+            .sum()
+    }
+
+    fn lower_bound(&self, partial: &HashMap<V, D>) -> Option<u64> {
+        Some(self.cost(partial))
+    }
+}
+
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug,
+    C: Clone + Constraint<V, D>,
+{
+    /// Add a constraint that solutions should, but need not, satisfy. Violating it costs `weight`.
+    pub fn add_soft_constraint(&mut self, constraint: C, weight: u64) -> Result<(), CspError<V>> {
+        self.validate_constraint(&constraint)?;
+        self.soft_constraints
+            .push(SoftConstraint { constraint, weight });
+
+        Ok(())
+    }
+
+    /// The total weight of the soft constraints `assignment` violates.
+    pub fn penalty(&self, assignment: &HashMap<V, D>) -> u64 {
+        Penalty {
+            soft_constraints: &self.soft_constraints,
+        }
+        .cost(assignment)
+    }
+
+    /// # Minimise Penalty
+    ///
+    /// Find the solution to the hard constraints that violates the least total weight of soft constraints.
+    ///
+    /// This is `minimise` with the penalty as the objective. Since a violated soft constraint stays violated
+    /// however the rest of the variables are assigned, the penalty of a partial assignment is a valid lower bound,
+    /// so branches that are already too costly get pruned.
+    pub fn minimise_penalty<F>(
+        &self,
+        config: SearchConfig,
+        on_improvement: F,
+    ) -> OptimisationOutcome<V, D, u64>
+    where
+        F: FnMut(&HashMap<V, D>, &u64),
+    {
+        let penalty = Penalty {
+            soft_constraints: &self.soft_constraints,
+        };
+
+        self.minimise(&penalty, config, on_improvement)
+    }
+}
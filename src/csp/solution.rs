@@ -0,0 +1,84 @@
+use std::collections::{btree_map, BTreeMap, HashMap};
+use std::{fmt, ops::Deref};
+
+/// # Solution
+///
+/// An assignment with its variables in order, for printing solutions or comparing them, e.g., in tests.
+/// The searches all return a `HashMap`, which lists its variables in a different order on every run;
+/// `Solution::from` sorts them.
+///
+/// It derefs to a `BTreeMap`, so `get`, `iter` and the like work as usual.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Solution<V, D>(BTreeMap<V, D>);
+
+impl<V, D> Solution<V, D> {
+    pub fn into_inner(self) -> BTreeMap<V, D> {
+        self.0
+    }
+}
+
+impl<V, D> Deref for Solution<V, D> {
+    type Target = BTreeMap<V, D>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<V, D> From<HashMap<V, D>> for Solution<V, D>
+where
+    V: Ord,
+{
+    fn from(assignment: HashMap<V, D>) -> Self {
+        assignment.into_iter().collect()
+    }
+}
+
+impl<V, D> FromIterator<(V, D)> for Solution<V, D>
+where
+    V: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (V, D)>,
+    {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<V, D> IntoIterator for Solution<V, D> {
+    type Item = (V, D);
+    type IntoIter = btree_map::IntoIter<V, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, V, D> IntoIterator for &'a Solution<V, D> {
+    type Item = (&'a V, &'a D);
+    type IntoIter = btree_map::Iter<'a, V, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// One `variable = value` line per variable, in order.
+impl<V, D> fmt::Display for Solution<V, D>
+where
+    V: fmt::Display,
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (variable, value)) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{variable} = {value}")?;
+        }
+
+        Ok(())
+    }
+}
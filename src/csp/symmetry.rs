@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::{fmt, hash::Hash};
+
+use super::{Constraint, CspError, CSP};
+
+/// # Symmetry
+///
+/// A way of turning any solution of a CSP into another one, declared with `CSP::add_symmetry`.
+///
+/// The search then only produces one solution out of each set of solutions that symmetries turn into one another,
+/// and cuts off every branch that can only lead to the others. Enumerating or counting the solutions of
+/// a map colouring with three interchangeable colours, for example, does about a sixth of the work and finds about a sixth of the solutions.
+///
+/// Which solution is kept is decided by the order of `CSP::variables` and the order of the values in each domain:
+/// it is the one that comes first when comparing the values of the variables one by one.
+/// So the variables swapped by a symmetry should have the same domain, in the same order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Symmetry<V, D> {
+    /// These values can be swapped for one another throughout any solution, e.g., the colours of a map colouring.
+    /// List them in the order they appear in the domains.
+    InterchangeableValues(Vec<D>),
+    /// Moving the value of each first variable to the second one turns any solution into another,
+    /// e.g., reflecting the board of N-queens from left to right. Variables left out keep their value.
+    Variables(Vec<(V, V)>),
+    /// Replacing each first value with the second one throughout turns any solution into another,
+    /// e.g., reflecting the board of N-queens from top to bottom. Values left out stay as they are.
+    Values(Vec<(D, D)>),
+}
+
+/// The symmetries declared on a CSP, along with how to tell values apart, which the rest of the search never needs to do.
+#[derive(Debug)]
+pub(super) struct SymmetryBreaking<V, D> {
+    symmetries: Vec<Symmetry<V, D>>,
+    // Only set once a symmetry is added, since that is the only place that requires `D: PartialEq`.
+    eq: Option<fn(&D, &D) -> bool>,
+}
+
+impl<V, D> Default for SymmetryBreaking<V, D> {
+    fn default() -> Self {
+        Self {
+            symmetries: Vec::new(),
+            eq: None,
+        }
+    }
+}
+
+impl<V, D> SymmetryBreaking<V, D>
+where
+    V: Hash + Eq,
+{
+    /// Whether `assignment` can still be extended to the one solution kept out of its symmetric ones.
+    /// Unassigned variables could still go either way, so they never rule anything out.
+    pub fn allows(&self, variables: &[V], domains: &[&[D]], assignment: &HashMap<V, D>) -> bool {
+        let Some(eq) = self.eq else {
+            return true;
+        };
+
+        self.symmetries.iter().all(|symmetry| match symmetry {
+            Symmetry::InterchangeableValues(values) => precedes(eq, values, variables, assignment),
+            Symmetry::Variables(pairs) => lex_leader(eq, variables, domains, assignment, |j| {
+                // The value that ends up in `variables[j]` is the one of whichever variable is moved there.
+                let from = pairs
+                    .iter()
+                    .find(|(_, to)| *to == variables[j])
+                    .map_or(&variables[j], |(from, _)| from);
+
+                assignment.get(from)
+            }),
+            Symmetry::Values(pairs) => lex_leader(eq, variables, domains, assignment, |j| {
+                assignment.get(&variables[j]).map(|value| {
+                    pairs
+                        .iter()
+                        .find(|(from, _)| eq(from, value))
+                        .map_or(value, |(_, to)| to)
+                })
+            }),
+        })
+    }
+}
+
+/// # Value Precedence
+///
+/// Interchangeable values can be renamed so that each is first used, going through `variables` in order,
+/// before the ones listed after it. Any assignment where one is used before an earlier one has a renamed twin that does not,
+/// so only those that respect the order of `values` are kept.
+fn precedes<V, D>(
+    eq: fn(&D, &D) -> bool,
+    values: &[D],
+    variables: &[V],
+    assignment: &HashMap<V, D>,
+) -> bool
+where
+    V: Hash + Eq,
+{
+    let mut next = 0;
+
+    for variable in variables {
+        let Some(value) = assignment.get(variable) else {
+            return true;
+        };
+
+        match values.iter().position(|other| eq(other, value)) {
+            Some(position) if position > next => return false,
+            Some(position) if position == next => next += 1,
+            _ => {}
+        }
+    }
+
+    true
+}
+
+/// # Lex Leader
+///
+/// Keep an assignment only if, going through `variables` in order, its values are no later in their domains
+/// than those of its image under a symmetry, which `image` gives for each position.
+/// Of an assignment and its image, at most one of them passes, unless they are the same.
+fn lex_leader<'a, V, D, F>(
+    eq: fn(&D, &D) -> bool,
+    variables: &[V],
+    domains: &[&[D]],
+    assignment: &'a HashMap<V, D>,
+    image: F,
+) -> bool
+where
+    V: Hash + Eq,
+    F: Fn(usize) -> Option<&'a D>,
+{
+    for (j, variable) in variables.iter().enumerate() {
+        let (Some(value), Some(image)) = (assignment.get(variable), image(j)) else {
+            return true;
+        };
+
+        let position = |value: &D| domains[j].iter().position(|other| eq(other, value));
+
+        match (position(value), position(image)) {
+            (Some(value), Some(image)) if value < image => return true,
+            (Some(value), Some(image)) if value > image => return false,
+            (Some(_), Some(_)) => {}
+            // A value outside the domain is not one the symmetry was declared for, so there is nothing to compare.
+            _ => return true,
+        }
+    }
+
+    true
+}
+
+/// Whether `pairs` moves every item to exactly one other and back, i.e., the items moved from are the items moved to, once each.
+fn is_permutation<T>(pairs: &[(T, T)], eq: impl Fn(&T, &T) -> bool) -> bool {
+    let froms: Vec<&T> = pairs.iter().map(|(from, _)| from).collect();
+    let tos: Vec<&T> = pairs.iter().map(|(_, to)| to).collect();
+
+    let distinct = |items: &[&T]| {
+        items
+            .iter()
+            .enumerate()
+            .all(|(index, item)| !items[..index].iter().any(|other| eq(other, item)))
+    };
+
+    distinct(&froms) && distinct(&tos) && froms.iter().all(|from| tos.iter().any(|to| eq(to, from)))
+}
+
+impl<V, D, C> CSP<V, D, C>
+where
+    V: Clone + fmt::Debug + Hash + Ord,
+    D: Clone + fmt::Debug + PartialEq,
+    C: Clone + Constraint<V, D>,
+{
+    /// # Add Symmetry
+    ///
+    /// Declare that `symmetry` turns every solution of this CSP into another one, so the search can skip the symmetric ones.
+    /// See `Symmetry`. Nothing checks that the symmetry really holds; one that does not can make the search miss solutions.
+    ///
+    /// With symmetries, `solutions` and `count_solutions` give one solution for each set of symmetric ones.
+    /// An initial assignment, as in `solutions_with_assignment`, only finds the solutions kept that extend it.
+    pub fn add_symmetry(&mut self, symmetry: Symmetry<V, D>) -> Result<(), CspError<V>> {
+        let known = |value: &D| self.domains.values().any(|domain| domain.contains(value));
+
+        match &symmetry {
+            Symmetry::InterchangeableValues(values) => {
+                let repeated = values
+                    .iter()
+                    .enumerate()
+                    .any(|(index, value)| values[..index].contains(value));
+
+                if repeated || !values.iter().all(known) {
+                    return Err(CspError::InvalidSymmetry);
+                }
+            }
+            Symmetry::Variables(pairs) => {
+                for (from, to) in pairs {
+                    for variable in [from, to] {
+                        if !self.constraints.contains_key(variable) {
+                            return Err(CspError::UnknownVariable(variable.clone()));
+                        }
+                    }
+                }
+
+                if !is_permutation(pairs, V::eq) {
+                    return Err(CspError::InvalidSymmetry);
+                }
+            }
+            Symmetry::Values(pairs) => {
+                if !is_permutation(pairs, D::eq) || !pairs.iter().all(|(from, _)| known(from)) {
+                    return Err(CspError::InvalidSymmetry);
+                }
+            }
+        }
+
+        self.symmetry.symmetries.push(symmetry);
+        self.symmetry.eq = Some(D::eq);
+
+        Ok(())
+    }
+
+    /// Every symmetry added with `add_symmetry`.
+    pub fn symmetries(&self) -> &[Symmetry<V, D>] {
+        &self.symmetry.symmetries
+    }
+}
@@ -1,7 +1,6 @@
 use std::{
-    cmp::{Ordering, Reverse},
+    cmp::Reverse,
     collections::{BinaryHeap, HashMap, HashSet, VecDeque},
-    fmt,
     hash::Hash,
 };
 
@@ -63,144 +62,122 @@ where
     return Ok(false);
 }
 
+/// # Search Tree
+///
+/// Every node a search pushed onto its frontier, in the order it pushed them, each with the index of its parent.
+/// Pushing a node only stores its state and two numbers, however long the path to it is.
 #[derive(Clone, Debug)]
-pub struct Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
+struct SearchTree<T> {
+    nodes: Vec<TreeNode<T>>,
+}
+
+#[derive(Clone, Debug)]
+struct TreeNode<T> {
     state: T,
-    parent: Option<Box<Node<T>>>,
+    parent: Option<usize>,
     cost: OrderedFloat<f64>,
-    heuristic: OrderedFloat<f64>,
 }
 
-impl<T> Default for Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
-    fn default() -> Self {
+impl<T> SearchTree<T> {
+    fn new(initial: T) -> Self {
         Self {
-            state: Default::default(),
-            parent: None,
-            cost: Default::default(),
-            heuristic: Default::default(),
+            nodes: vec![TreeNode {
+                state: initial,
+                parent: None,
+                cost: OrderedFloat(0.0),
+            }],
         }
     }
-}
 
-/*
-Rust's equivalent of Java's `Comparable` interface is more or less the combination of Rust's `PartialEq`, `Eq`,
-`PartialOrd`, and possibly `Ord` traits.
+    /// Add a node reached from the node at `parent`, and return its index.
+    fn push(&mut self, state: T, parent: usize, cost: OrderedFloat<f64>) -> usize {
+        self.nodes.push(TreeNode {
+            state,
+            parent: Some(parent),
+            cost,
+        });
 
-Note that we are comparing ONLY the cost and heuristic / estimated cost of the nodes.
-If you `#[derive]` these traits, you will see strange results because the comparison will use ALL of the node's fields.
-*/
+        self.nodes.len() - 1
+    }
 
-impl<T> PartialEq for Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
-    fn eq(&self, other: &Self) -> bool {
-        let my_cost = self.cost + self.heuristic;
-        let others_cost = other.cost + other.heuristic;
+    fn state(&self, index: usize) -> &T {
+        &self.nodes[index].state
+    }
 
-        my_cost == others_cost
+    fn cost(&self, index: usize) -> OrderedFloat<f64> {
+        self.nodes[index].cost
     }
-}
 
-impl<T> Eq for Node<T> where T: Clone + fmt::Debug + Default + PartialEq {}
+    /// The `Node` at `index`, with the states on the path to it moved out of the tree, so the rest of the tree can be dropped.
+    fn into_node(self, index: usize) -> Node<T> {
+        let cost = self.cost(index);
 
-impl<T> PartialOrd for Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let my_cost = self.cost + self.heuristic;
-        let others_cost = other.cost + other.heuristic;
+        let mut on_path = vec![false; self.nodes.len()];
+        let mut current = Some(index);
 
-        Some(my_cost.cmp(&others_cost))
-    }
-}
+        while let Some(node) = current {
+            on_path[node] = true;
+            current = self.nodes[node].parent;
+        }
 
-impl<T> Ord for Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq + Eq + PartialOrd,
-{
-    fn cmp(&self, other: &Self) -> Ordering {
-        let my_cost = self.cost + self.heuristic;
-        let others_cost = other.cost + other.heuristic;
+        // A parent always comes before its children, so the path is already in order.
+        let path = self
+            .nodes
+            .into_iter()
+            .zip(on_path)
+            .filter_map(|(node, on_path)| on_path.then_some(node.state))
+            .collect();
 
-        my_cost.cmp(&others_cost)
+        Node { path, cost }
     }
 }
 
-impl<T> Iterator for Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
-    type Item = Box<Node<T>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.parent.clone()
-    }
+/// # Node
+///
+/// The node a search found its goal at: the path the search took to get there, and what that path cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node<T> {
+    /// From the initial state to this node's state, so never empty.
+    path: Vec<T>,
+    cost: OrderedFloat<f64>,
 }
 
-impl<T> Node<T>
-where
-    T: Clone + fmt::Debug + Default + PartialEq,
-{
-    pub fn new(state: T, parent: Option<Box<Node<T>>>) -> Self {
-        Self {
-            state,
-            parent,
-            ..Default::default()
-        }
-    }
+impl<T> Node<T> {
+    /// The node at the end of `path`, each state of which was reached from the one before it with a move that costs 1.
+    fn from_path(path: Vec<T>) -> Self {
+        let cost = OrderedFloat(path.len().saturating_sub(1) as f64);
 
-    pub fn new_with_cost_and_heuristic(
-        state: T,
-        parent: Option<Box<Node<T>>>,
-        cost: OrderedFloat<f64>,
-        heuristic: OrderedFloat<f64>,
-    ) -> Self {
-        Self {
-            state,
-            parent,
-            cost,
-            heuristic,
-        }
+        Self { path, cost }
     }
 
-    /// Traverse this node's parents, transforming them into a list of nodes
-    /// that you can then iterate.
-    /// (Deriving `Iterator` would also work)
-    pub fn to_node_path(&self) -> VecDeque<Node<T>> {
-        let mut path = VecDeque::<Node<T>>::new();
-
-        path.push_front(self.clone());
-
-        let mut current_node = self.clone();
-
-        while let Some(node) = current_node.parent {
-            current_node = *node.clone();
-            path.push_front(*node);
-        }
-
-        path
+    pub fn state(&self) -> &T {
+        self.path
+            .last()
+            .expect("a path starts at the initial state")
     }
 
-    pub fn to_path(&self) -> VecDeque<T> {
-        let node_path = self.to_node_path();
+    /// The cost of the path from the initial state to this node:
+    /// the number of moves for `dfs`, `bfs`, `iddfs`, `astar` and `ida_star`,
+    /// and their total cost for `astar_with_costs` and `dijkstra`.
+    pub fn cost(&self) -> OrderedFloat<f64> {
+        self.cost
+    }
 
-        node_path.iter().map(|node| node.clone().state).collect()
+    /// The states from the initial state to this node.
+    pub fn path(&self) -> &[T] {
+        &self.path
     }
 
-    pub fn get_total_cost_of_path(&self) -> OrderedFloat<f64> {
-        let node_path = self.to_node_path();
+    pub fn into_path(self) -> Vec<T> {
+        self.path
+    }
 
-        node_path
-            .iter()
-            .fold(OrderedFloat(0.0), |acc, node| acc + node.cost)
+    /// The states from the initial state to this node, as the book lists them.
+    pub fn to_path(&self) -> VecDeque<T>
+    where
+        T: Clone,
+    {
+        self.path.iter().cloned().collect()
     }
 }
 
@@ -209,7 +186,7 @@ where
 /// Use `goal_test_fn` to search for a value in a given structure,
 /// given an `initial` state and `get_successors_fn` that describes how to reach a value's neighbours.
 ///
-/// Returns `Some` if the goal is found, and `None` otherwise. You can traverse the returned `Node`'s `parent`s to get
+/// Returns `Some` if the goal is found, and `None` otherwise. Call `to_path` on the returned `Node` to get
 /// the path the algorithm took.
 ///
 pub fn dfs<T, PredicateFn, SuccessorsFn>(
@@ -218,40 +195,33 @@ pub fn dfs<T, PredicateFn, SuccessorsFn>(
     get_successors_fn: SuccessorsFn,
 ) -> Option<Node<T>>
 where
-    T: Clone + fmt::Debug + Default + Eq + Hash + PartialEq,
+    T: Clone + Eq + Hash,
     PredicateFn: Fn(T) -> bool,
     SuccessorsFn: Fn(T) -> Vec<T>,
 {
-    let mut frontier = Vec::<Node<T>>::new();
-
-    frontier.push(Node::<T>::new(initial.clone(), None));
+    let mut tree = SearchTree::new(initial.clone());
+    // The frontier only holds indices into `tree`.
+    let mut frontier = vec![0];
 
     let mut explored = HashSet::<T>::new();
 
     explored.insert(initial);
 
-    while !frontier.is_empty() {
-        let current_node = frontier.pop();
-
-        //println!("[DFS] Current node: {:?}", (current_node.clone()?).state);
+    while let Some(current) = frontier.pop() {
+        let current_state = tree.state(current).clone();
 
-        if let Some(current_node) = current_node {
-            let current_state = &current_node.state;
+        if goal_test_fn(current_state.clone()) {
+            return Some(tree.into_node(current));
+        }
 
-            if goal_test_fn(current_state.clone()) {
-                return Some(current_node);
+        for succ in get_successors_fn(current_state) {
+            if !explored.insert(succ.clone()) {
+                continue;
             }
 
-            for succ in get_successors_fn(current_state.clone()) {
-                if !explored.insert(succ.clone()) {
-                    //println!("[DFS] I already explored {:?}", succ);
-                    continue;
-                }
-
-                //println!("[DFS] Pushing {:?}", succ);
+            let cost = tree.cost(current) + 1.0;
 
-                frontier.push(Node::<T>::new(succ, Some(Box::new(current_node.clone()))));
-            }
+            frontier.push(tree.push(succ, current, cost));
         }
     }
 
@@ -263,7 +233,7 @@ where
 /// Use `goal_test_fn` to search for a value in a given structure,
 /// given an `initial` state and `get_successors_fn` that describes how to reach a value's neighbours.
 ///
-/// Returns `Some` if the goal is found, and `None` otherwise. You can traverse the returned `Node`'s `parent`s to get
+/// Returns `Some` if the goal is found, and `None` otherwise. Call `to_path` on the returned `Node` to get
 /// the path the algorithm took.
 ///
 pub fn bfs<T, PredicateFn, SuccessorsFn>(
@@ -272,37 +242,33 @@ pub fn bfs<T, PredicateFn, SuccessorsFn>(
     get_successors_fn: SuccessorsFn,
 ) -> Option<Node<T>>
 where
-    T: Clone + fmt::Debug + Default + Eq + Hash + PartialEq,
+    T: Clone + Eq + Hash,
     PredicateFn: Fn(T) -> bool,
     SuccessorsFn: Fn(T) -> Vec<T>,
 {
-    let mut frontier = VecDeque::<Node<T>>::new();
-
-    frontier.push_back(Node::<T>::new(initial.clone(), None));
+    let mut tree = SearchTree::new(initial.clone());
+    // The frontier only holds indices into `tree`.
+    let mut frontier = VecDeque::from([0]);
 
     let mut explored = HashSet::<T>::new();
 
     explored.insert(initial);
 
-    while !frontier.is_empty() {
-        let current_node = frontier.pop_front();
+    while let Some(current) = frontier.pop_front() {
+        let current_state = tree.state(current).clone();
 
-        if let Some(current_node) = current_node {
-            let current_state = current_node.clone().state;
+        if goal_test_fn(current_state.clone()) {
+            return Some(tree.into_node(current));
+        }
 
-            if goal_test_fn(current_state.clone()) {
-                return Some(current_node);
+        for succ in get_successors_fn(current_state) {
+            if !explored.insert(succ.clone()) {
+                continue;
             }
 
-            for succ in get_successors_fn(current_state) {
-                if explored.contains(&succ) {
-                    continue;
-                }
-
-                explored.insert(succ.clone());
+            let cost = tree.cost(current) + 1.0;
 
-                frontier.push_back(Node::<T>::new(succ, Some(Box::new(current_node.clone()))));
-            }
+            frontier.push_back(tree.push(succ, current, cost));
         }
     }
 
@@ -315,7 +281,11 @@ where
 /// given an `initial` state and movement cost and `get_successors_fn` that describes how to reach a value's neighbours,
 /// and a `heuristic_fn` that describes the estimated cost of moving from one node to the goal node.
 ///
-/// Returns `Some` if the goal is found, and `None` otherwise. You can traverse the returned `Node`'s `parent`s to get
+/// Every move costs 1, as on a regular grid; see `astar_with_costs` for moves that cost different amounts.
+/// With a heuristic that never overestimates the number of moves left, e.g., the Manhattan distance on a grid,
+/// the path found is a shortest one, as long as the one `bfs` finds.
+///
+/// Returns `Some` if the goal is found, and `None` otherwise. Call `to_path` on the returned `Node` to get
 /// the path the algorithm took.
 ///
 pub fn astar<T, PredicateFn, SuccessorsFn, HeuristicFn>(
@@ -325,85 +295,385 @@ pub fn astar<T, PredicateFn, SuccessorsFn, HeuristicFn>(
     heuristic_fn: HeuristicFn,
 ) -> Option<Node<T>>
 where
-    T: Clone + fmt::Debug + Default + Hash + Ord,
+    T: Clone + Eq + Hash,
     PredicateFn: Fn(T) -> bool,
     SuccessorsFn: Fn(T) -> Vec<T>,
     HeuristicFn: Fn(T) -> OrderedFloat<f64>,
 {
-    // Note that this uses `std::cmp::Reverse` to create a min-heap,
-    // so that it sorts nodes by cost in ascending order, as described by `Node`'s `PartialOrd` and `Ord` traits.
+    astar_with_costs(
+        initial,
+        goal_test_fn,
+        |state| {
+            get_successors_fn(state)
+                .into_iter()
+                .map(|succ| (succ, OrderedFloat(1.0)))
+                .collect()
+        },
+        heuristic_fn,
+    )
+}
+
+/// # A-Star Search With Costs
+///
+/// Like `astar`, but `get_successors_fn` gives the cost of moving to each neighbour along with it,
+/// e.g., rough terrain on a map, the length of a road, or the weight of an edge in a graph.
+/// Costs must not be negative.
+///
+/// Returns the goal's `Node` with the cheapest path found, whose `cost` is the total cost of that path.
+/// It is the cheapest path there is as long as `heuristic_fn` never overestimates the cost of reaching the goal,
+/// i.e., it is admissible.
+///
+/// Each state is expanded (its successors generated) once it is taken off the frontier and put in the closed set,
+/// and then again only if a cheaper path to it turns up later, which takes it back out of the closed set.
+/// That only happens when the heuristic is admissible but not consistent, i.e., it drops by more than the cost of some move;
+/// the distance to the goal on a grid, for instance, never does.
+///
+pub fn astar_with_costs<T, PredicateFn, SuccessorsFn, HeuristicFn>(
+    initial: T,
+    goal_test_fn: PredicateFn,
+    get_successors_fn: SuccessorsFn,
+    heuristic_fn: HeuristicFn,
+) -> Option<Node<T>>
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<(T, OrderedFloat<f64>)>,
+    HeuristicFn: Fn(T) -> OrderedFloat<f64>,
+{
+    let (tree, _, goal) = best_first(initial, goal_test_fn, get_successors_fn, heuristic_fn);
+
+    goal.map(|goal| tree.into_node(goal))
+}
+
+/// # Dijkstra's Algorithm
+///
+/// Uniform-cost search: like `astar_with_costs`, but without a heuristic,
+/// so it always expands whichever node has the cheapest path so far, for problems where there is no good estimate
+/// of the cost left. Costs must not be negative.
+///
+/// Returns the goal's `Node` with the cheapest path there is, whose `cost` is the total cost of that path,
+/// or `None` if no goal can be reached.
+///
+pub fn dijkstra<T, PredicateFn, SuccessorsFn>(
+    initial: T,
+    goal_test_fn: PredicateFn,
+    get_successors_fn: SuccessorsFn,
+) -> Option<Node<T>>
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<(T, OrderedFloat<f64>)>,
+{
+    astar_with_costs(initial, goal_test_fn, get_successors_fn, |_| {
+        OrderedFloat(0.0)
+    })
+}
+
+/// # Shortest Paths
+///
+/// The cheapest paths from one state to every state that can be reached from it, as found by `dijkstra_all`.
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<T> {
+    /// The cost of the cheapest path to each state that can be reached, including 0 for the initial state.
+    pub distances: HashMap<T, OrderedFloat<f64>>,
+    /// The state each state comes right after on its cheapest path, for every state but the initial one.
+    pub predecessors: HashMap<T, T>,
+}
+
+impl<T> ShortestPaths<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Follow the predecessors of `state` back to the initial state, and list them from there to `state`.
+    /// Returns `None` if `state` cannot be reached.
+    pub fn path_to(&self, state: &T) -> Option<VecDeque<T>> {
+        if !self.distances.contains_key(state) {
+            return None;
+        }
+
+        let mut path = VecDeque::from([state.clone()]);
+
+        while let Some(predecessor) = self.predecessors.get(&path[0]) {
+            path.push_front(predecessor.clone());
+        }
+
+        Some(path)
+    }
+}
+
+/// # Dijkstra's Algorithm For All Targets
+///
+/// Like `dijkstra`, but without a goal: it goes on until it has found the cheapest path to every state
+/// that can be reached from `initial`. Costs must not be negative, and there must be finitely many states to reach.
+///
+pub fn dijkstra_all<T, SuccessorsFn>(
+    initial: T,
+    get_successors_fn: SuccessorsFn,
+) -> ShortestPaths<T>
+where
+    T: Clone + Eq + Hash,
+    SuccessorsFn: Fn(T) -> Vec<(T, OrderedFloat<f64>)>,
+{
+    let (tree, best, _) = best_first(initial, |_| false, get_successors_fn, |_| OrderedFloat(0.0));
+
+    let mut distances = HashMap::<T, OrderedFloat<f64>>::new();
+    let mut predecessors = HashMap::<T, T>::new();
+
+    for (state, index) in best {
+        if let Some(parent) = tree.nodes[index].parent {
+            predecessors.insert(state.clone(), tree.state(parent).clone());
+        }
+
+        distances.insert(state, tree.cost(index));
+    }
+
+    ShortestPaths {
+        distances,
+        predecessors,
+    }
+}
+
+/// The search behind `astar_with_costs`, `dijkstra` and `dijkstra_all`.
+///
+/// Returns every node it pushed, the index of the cheapest node for each state it reached,
+/// and the index of the goal's node if it found one.
+fn best_first<T, PredicateFn, SuccessorsFn, HeuristicFn>(
+    initial: T,
+    goal_test_fn: PredicateFn,
+    get_successors_fn: SuccessorsFn,
+    heuristic_fn: HeuristicFn,
+) -> (SearchTree<T>, HashMap<T, usize>, Option<usize>)
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<(T, OrderedFloat<f64>)>,
+    HeuristicFn: Fn(T) -> OrderedFloat<f64>,
+{
+    let mut tree = SearchTree::new(initial.clone());
+
+    // The frontier holds the estimated cost of the whole path through each node (its cost plus its heuristic),
+    // along with its index in `tree`, which breaks ties in favour of the nodes pushed first.
+    // Note that this uses `std::cmp::Reverse` to create a min-heap, so that it sorts nodes by cost in ascending order.
     // The default is descending order, so the algorithm may appear to choose the *worst* possible path,
     // which is obviously not what we want in a proper A* implementation!
-    let mut frontier = BinaryHeap::<Reverse<Node<T>>>::new();
+    let mut frontier = BinaryHeap::<Reverse<(OrderedFloat<f64>, usize)>>::new();
+
+    frontier.push(Reverse((heuristic_fn(initial.clone()), 0)));
+
+    // The node with the cheapest path found so far to each state, whether it is in the frontier or the closed set.
+    let mut best = HashMap::<T, usize>::new();
+    // The states that were expanded with their node in `best`.
+    let mut closed = HashSet::<T>::new();
 
-    frontier.push(Reverse(Node::<T>::new_with_cost_and_heuristic(
-        initial.clone(),
-        None,
-        OrderedFloat(0.0),
-        heuristic_fn(initial.clone()),
-    )));
+    best.insert(initial, 0);
 
-    let mut explored = HashMap::<T, OrderedFloat<f64>>::new();
+    while let Some(Reverse((_, current))) = frontier.pop() {
+        let current_state = tree.state(current).clone();
+        let current_cost = tree.cost(current);
 
-    explored.insert(initial, OrderedFloat(0.0));
+        // `BinaryHeap` cannot lower the cost of a node already in it (a "decrease-key"),
+        // so a cheaper path to a state pushes another node for it instead. With the same heuristic and a lower cost,
+        // that one comes off the frontier first, and the more expensive ones left behind find the state closed.
+        if !closed.insert(current_state.clone()) {
+            continue;
+        }
+
+        if goal_test_fn(current_state.clone()) {
+            return (tree, best, Some(current));
+        }
 
-    while !frontier.is_empty() {
-        let current_node = frontier.pop();
+        for (succ, step_cost) in get_successors_fn(current_state) {
+            debug_assert!(step_cost >= OrderedFloat(0.0), "negative cost {step_cost}");
 
-        //println!("Current node: {:?}", current_node);
+            let new_cost = current_cost + step_cost;
 
-        if let Some(current_node) = current_node {
-            let current_state = current_node.clone().0.state;
+            // If we have not reached this location yet,
+            // OR if we have AND this path is cheaper, push the node.
+            if best.get(&succ).is_none_or(|&old| new_cost < tree.cost(old)) {
+                // If it was already expanded, reopen it, so that the cheaper path reaches its successors too.
+                closed.remove(&succ);
 
-            //println!("I am at {:?}", current_state);
+                let estimate = new_cost + heuristic_fn(succ.clone());
+                let index = tree.push(succ.clone(), current, new_cost);
 
-            if goal_test_fn(current_state.clone()) {
-                return Some(current_node.0);
+                best.insert(succ, index);
+                frontier.push(Reverse((estimate, index)));
             }
+        }
+    }
 
-            for succ in get_successors_fn(current_state.clone()) {
-                // Since we are using a regular grid with no cost to move from one tile to another,
-                // an added cost of 1 is fine.
-                // If there are obstructions between nodes, a `cost_fn` would make more sense here.
-                // For instance, if there was a patch of rough terrain between two nodes, that move
-                // would be more expensive than if the path was clear.
-                // Or, for another example, if we were making a map where our nodes represent places on that map,
-                // we would want to consider traffic and delays on the route here, which can make an otherwise optimal
-                // route more expensive.
-                let new_cost = current_node.0.cost + 1.0;
-
-                // println!(
-                //     "[A*] Cost for current node: {:?}, {:?}",
-                //     current_node.0.cost, current_node.0.state
-                // );
-                // println!("[A*] Cost for next node: {:?}, {:?}", new_cost, succ);
-
-                let old_cost = *explored.get(&succ).unwrap_or(&OrderedFloat(0.0));
-
-                //println!("[A*] Old cost: {:?}", old_cost);
-
-                // If we have not explored this location yet,
-                // OR if we have AND it has a lower cost, push the node
-                if !explored.contains_key(&succ) || (old_cost != 0.0 && old_cost > new_cost) {
-                    explored.insert(succ.clone(), new_cost);
-
-                    // println!(
-                    //     "[A*] I just explored {:?}, cost {:?}. old cost {:?}",
-                    //     succ, new_cost, old_cost
-                    // );
-
-                    frontier.push(Reverse(Node::<T>::new_with_cost_and_heuristic(
-                        succ.clone(),
-                        Some(Box::new(current_node.0.clone())),
-                        new_cost,
-                        heuristic_fn(succ.clone()),
-                    )));
-                }
+    (tree, best, None)
+}
+
+/// # Iterative Deepening
+///
+/// What `iddfs` or `ida_star` found, along with how many depth-first searches it took.
+#[derive(Clone, Debug)]
+pub struct IterativeDeepening<T> {
+    /// The goal's `Node`, if one was found within the limit.
+    pub solution: Option<Node<T>>,
+    /// The number of depth-first searches run, each with a higher limit than the last.
+    pub iterations: usize,
+    /// Whether the last search reached every path without being cut off, so that raising the limit would not help:
+    /// no goal can be reached at all. Always `false` when a goal was found.
+    pub exhausted: bool,
+}
 
-                //println!("======================");
+/// # Iterative Deepening Depth First Search
+///
+/// Use `goal_test_fn` to search for a value in a given structure,
+/// given an `initial` state and `get_successors_fn` that describes how to reach a value's neighbours.
+///
+/// Runs `dfs` over and over, going one move deeper each time, up to `max_depth` moves. Like `bfs`, it finds a path with
+/// as few moves as there can be, but like `dfs`, it only keeps the path it is on in memory, rather than every state it reached.
+/// The price is searching the shallow states again on every iteration, and searching states again whenever different paths
+/// lead to them, since it only avoids going round in circles on the path it is on.
+///
+/// Returns the goal's `Node`, if any, along with the number of iterations; see `IterativeDeepening`.
+///
+pub fn iddfs<T, PredicateFn, SuccessorsFn>(
+    initial: T,
+    goal_test_fn: PredicateFn,
+    get_successors_fn: SuccessorsFn,
+    max_depth: usize,
+) -> IterativeDeepening<T>
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<T>,
+{
+    // Without a heuristic, every path of a given depth has the same cost, so IDA* goes one move deeper each iteration.
+    ida_star(
+        initial,
+        goal_test_fn,
+        get_successors_fn,
+        |_| OrderedFloat(0.0),
+        OrderedFloat(max_depth as f64),
+    )
+}
+
+/// # Iterative Deepening A-Star Search
+///
+/// Use `goal_test_fn` to search for a value in a given structure,
+/// given an `initial` state and `get_successors_fn` that describes how to reach a value's neighbours,
+/// and a `heuristic_fn` that describes the estimated cost of moving from one node to the goal node.
+///
+/// Runs `dfs` over and over, cutting off every path whose cost plus heuristic is above a threshold. The threshold starts at
+/// the heuristic of `initial`, and each iteration raises it to the lowest one that was cut off, up to `max_cost`.
+/// With a heuristic that never overestimates, it finds a path as short as the one `astar` finds,
+/// but only keeps the path it is on in memory, as `iddfs` does.
+///
+/// Every move costs 1. Returns the goal's `Node`, if any, along with the number of iterations; see `IterativeDeepening`.
+///
+pub fn ida_star<T, PredicateFn, SuccessorsFn, HeuristicFn>(
+    initial: T,
+    goal_test_fn: PredicateFn,
+    get_successors_fn: SuccessorsFn,
+    heuristic_fn: HeuristicFn,
+    max_cost: OrderedFloat<f64>,
+) -> IterativeDeepening<T>
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<T>,
+    HeuristicFn: Fn(T) -> OrderedFloat<f64>,
+{
+    let mut threshold = heuristic_fn(initial.clone());
+    let mut iterations = 0;
+
+    while threshold <= max_cost {
+        iterations += 1;
+
+        match bounded_dfs(
+            initial.clone(),
+            &goal_test_fn,
+            &get_successors_fn,
+            &heuristic_fn,
+            threshold,
+        ) {
+            Ok(solution) => {
+                return IterativeDeepening {
+                    solution: Some(solution),
+                    iterations,
+                    exhausted: false,
+                }
+            }
+            Err(Some(next_threshold)) => threshold = next_threshold,
+            Err(None) => {
+                return IterativeDeepening {
+                    solution: None,
+                    iterations,
+                    exhausted: true,
+                }
             }
         }
     }
 
-    None
+    IterativeDeepening {
+        solution: None,
+        iterations,
+        exhausted: false,
+    }
+}
+
+/// One iteration of `ida_star`: a depth first search that cuts off every path whose cost plus heuristic is above `threshold`.
+///
+/// Returns the goal's `Node` if it finds one, and otherwise the lowest cost plus heuristic that was cut off,
+/// or `None` if nothing was.
+fn bounded_dfs<T, PredicateFn, SuccessorsFn, HeuristicFn>(
+    initial: T,
+    goal_test_fn: &PredicateFn,
+    get_successors_fn: &SuccessorsFn,
+    heuristic_fn: &HeuristicFn,
+    threshold: OrderedFloat<f64>,
+) -> Result<Node<T>, Option<OrderedFloat<f64>>>
+where
+    T: Clone + Eq + Hash,
+    PredicateFn: Fn(T) -> bool,
+    SuccessorsFn: Fn(T) -> Vec<T>,
+    HeuristicFn: Fn(T) -> OrderedFloat<f64>,
+{
+    // The path from `initial` to the state being searched, each state with the successors left to try from it.
+    // This, and the same states in `on_path`, is all the search keeps in memory.
+    let mut path = Vec::<(T, std::vec::IntoIter<T>)>::new();
+    let mut on_path = HashSet::<T>::new();
+    let mut next_threshold: Option<OrderedFloat<f64>> = None;
+
+    let mut next = Some(initial);
+
+    loop {
+        if let Some(state) = next.take() {
+            let cost = OrderedFloat(path.len() as f64);
+            let estimate = cost + heuristic_fn(state.clone());
+
+            if estimate > threshold {
+                next_threshold =
+                    Some(next_threshold.map_or(estimate, |lowest| lowest.min(estimate)));
+            } else if goal_test_fn(state.clone()) {
+                let mut states: Vec<T> = path.into_iter().map(|(state, _)| state).collect();
+
+                states.push(state);
+
+                return Ok(Node::from_path(states));
+            } else {
+                on_path.insert(state.clone());
+                path.push((state.clone(), get_successors_fn(state).into_iter()));
+            }
+        }
+
+        let Some((_, successors)) = path.last_mut() else {
+            return Err(next_threshold);
+        };
+
+        match successors.next() {
+            // Going back to a state already on the path would only go round in circles.
+            Some(succ) if on_path.contains(&succ) => {}
+            Some(succ) => next = Some(succ),
+            None => {
+                if let Some((state, _)) = path.pop() {
+                    on_path.remove(&state);
+                }
+            }
+        }
+    }
 }
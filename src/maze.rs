@@ -99,9 +99,24 @@ impl Maze {
         goal: MazeLocation,
         sparseness: f64,
     ) -> Self {
+        Self::new_with_setup_and_rng(rows, columns, start, goal, sparseness, &mut thread_rng())
+    }
+
+    /// Like `new_with_setup`, but blocks cells using `rng`, e.g., a seeded one to get the same maze every time.
+    pub fn new_with_setup_and_rng<R>(
+        rows: usize,
+        columns: usize,
+        start: MazeLocation,
+        goal: MazeLocation,
+        sparseness: f64,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: Rng,
+    {
         let mut maze = Self::new_with_with_and_height(rows, columns);
 
-        maze.randomly_block_cells(sparseness);
+        maze.randomly_block_cells(sparseness, rng);
 
         maze.grid[start.row as usize][start.column as usize] = Cell::Start;
         maze.grid[goal.row as usize][goal.column as usize] = Cell::Goal;
@@ -115,9 +130,10 @@ impl Maze {
 
     /// Randomly block cells in this maze, given a threshold sparseness.
     /// This is the `randomly_fill()` method in the Java implementation.
-    fn randomly_block_cells(&mut self, sparseness: f64) {
-        let mut rng = thread_rng();
-
+    fn randomly_block_cells<R>(&mut self, sparseness: f64, rng: &mut R)
+    where
+        R: Rng,
+    {
         for row in &mut self.grid {
             for col in row {
                 if rng.gen::<f64>() < sparseness {
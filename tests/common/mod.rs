@@ -0,0 +1,67 @@
+//! Problems shared by the CSP tests.
+#![allow(dead_code)]
+
+use classic_computer_science_problems::csp::{StandardConstraint, CSP};
+
+pub type Csp<V, D> = CSP<V, D, StandardConstraint<V, D>>;
+
+pub fn australia() -> Csp<&'static str, &'static str> {
+    let regions = vec![
+        "Western Australia",
+        "Northern Territory",
+        "South Australia",
+        "Queensland",
+        "New South Wales",
+        "Victoria",
+        "Tasmania",
+    ];
+    let domains = regions
+        .iter()
+        .map(|&region| (region, vec!["red", "green", "blue"]))
+        .collect();
+
+    let mut csp = CSP::new(regions, domains).unwrap();
+
+    for (first, second) in [
+        ("Western Australia", "Northern Territory"),
+        ("Western Australia", "South Australia"),
+        ("South Australia", "Northern Territory"),
+        ("Queensland", "Northern Territory"),
+        ("Queensland", "South Australia"),
+        ("Queensland", "New South Wales"),
+        ("New South Wales", "South Australia"),
+        ("Victoria", "South Australia"),
+        ("Victoria", "New South Wales"),
+        ("Victoria", "Tasmania"),
+    ] {
+        csp.add_constraint(StandardConstraint::not_equal(first, second))
+            .unwrap();
+    }
+
+    csp
+}
+
+pub fn queens(n: i32) -> Csp<i32, i32> {
+    let columns: Vec<i32> = (1..=n).collect();
+    let domains = columns
+        .iter()
+        .map(|&column| (column, columns.clone()))
+        .collect();
+
+    let mut csp = CSP::new(columns.clone(), domains).unwrap();
+
+    for (index, &first) in columns.iter().enumerate() {
+        for &second in &columns[index + 1..] {
+            let distance = second - first;
+
+            csp.add_constraint(StandardConstraint::predicate(
+                first,
+                second,
+                move |a: &i32, b: &i32| a != b && (a - b).abs() != distance,
+            ))
+            .unwrap();
+        }
+    }
+
+    csp
+}
@@ -0,0 +1,204 @@
+//! Regression tests pinning the first solution of well-known problems,
+//! so a change to the search that changes which solution comes first does not go unnoticed.
+mod common;
+
+use std::collections::HashMap;
+
+use classic_computer_science_problems::csp::{
+    Backtracking, Cnf, Inference, Problem, Relation, Solution, StandardConstraint, ValueOrdering,
+    VariableOrdering, CSP,
+};
+
+use common::{australia, queens, Csp};
+
+fn send_more_money() -> Csp<char, i64> {
+    let letters = vec!['S', 'E', 'N', 'D', 'M', 'O', 'R', 'Y'];
+    let mut domains: HashMap<char, Vec<i64>> = letters
+        .iter()
+        .map(|&letter| (letter, (0..=9).collect()))
+        .collect();
+
+    domains.insert('M', vec![1]);
+
+    let mut csp = CSP::new(letters.clone(), domains).unwrap();
+
+    csp.add_constraint(StandardConstraint::all_different(letters))
+        .unwrap();
+    csp.add_constraint(StandardConstraint::linear(
+        vec![
+            (1000, 'S'),
+            (91, 'E'),
+            (-90, 'N'),
+            (1, 'D'),
+            (-9000, 'M'),
+            (-900, 'O'),
+            (10, 'R'),
+            (-1, 'Y'),
+        ],
+        Relation::Equal,
+        0,
+    ))
+    .unwrap();
+
+    csp
+}
+
+/// Every combination of search settings, to check that none of them brings back `HashMap` order.
+fn settings() -> Vec<(VariableOrdering, ValueOrdering, Inference, Backtracking)> {
+    let mut settings = Vec::new();
+
+    for variable_ordering in [
+        VariableOrdering::FirstUnassigned,
+        VariableOrdering::MinimumRemainingValuesThenDegree,
+    ] {
+        for value_ordering in [
+            ValueOrdering::DomainOrder,
+            ValueOrdering::LeastConstrainingValue,
+        ] {
+            for inference in [
+                Inference::None,
+                Inference::ForwardChecking,
+                Inference::MaintainArcConsistency,
+            ] {
+                for backtracking in [
+                    Backtracking::Chronological,
+                    Backtracking::ConflictDirected {
+                        record_nogoods: true,
+                    },
+                ] {
+                    settings.push((variable_ordering, value_ordering, inference, backtracking));
+                }
+            }
+        }
+    }
+
+    settings
+}
+
+#[test]
+fn australia_first_solution() {
+    let solution = Solution::from(australia().backtracking_search().unwrap());
+
+    assert_eq!(
+        solution.to_string(),
+        "New South Wales = green\n\
+         Northern Territory = green\n\
+         Queensland = red\n\
+         South Australia = blue\n\
+         Tasmania = green\n\
+         Victoria = red\n\
+         Western Australia = red"
+    );
+}
+
+#[test]
+fn eight_queens_first_solution() {
+    let solution = Solution::from(queens(8).backtracking_search().unwrap());
+    let rows: Vec<i32> = solution.values().copied().collect();
+
+    assert_eq!(rows, [1, 5, 8, 6, 3, 7, 2, 4]);
+}
+
+#[test]
+fn eight_queens_solution_count() {
+    assert_eq!(queens(8).count_solutions(), 92);
+}
+
+#[test]
+fn send_more_money_solution() {
+    let mut csp = send_more_money();
+
+    csp.variable_ordering = VariableOrdering::MinimumRemainingValues;
+    csp.inference = Inference::ForwardChecking;
+
+    let solution = Solution::from(csp.backtracking_search().unwrap());
+
+    assert_eq!(
+        solution.into_iter().collect::<Vec<_>>(),
+        [
+            ('D', 7),
+            ('E', 5),
+            ('M', 1),
+            ('N', 6),
+            ('O', 0),
+            ('R', 8),
+            ('S', 9),
+            ('Y', 2)
+        ]
+    );
+}
+
+#[test]
+fn same_first_solution_every_time() {
+    for (variable_ordering, value_ordering, inference, backtracking) in settings() {
+        let first = |csp: &mut Csp<&'static str, &'static str>| {
+            csp.variable_ordering = variable_ordering;
+            csp.value_ordering = value_ordering;
+            csp.inference = inference;
+            csp.backtracking = backtracking;
+
+            csp.backtracking_search().map(Solution::from)
+        };
+
+        // Each CSP has its own `HashMap`s, with their own random order.
+        let expected = first(&mut australia());
+
+        for _ in 0..10 {
+            assert_eq!(first(&mut australia()), expected);
+        }
+    }
+}
+
+#[test]
+fn same_solutions_in_the_same_order_every_time() {
+    let expected: Vec<Solution<i32, i32>> = queens(6).solutions().map(Solution::from).collect();
+
+    for _ in 0..10 {
+        let csp = queens(6);
+
+        assert!(csp.solutions().map(Solution::from).eq(expected.clone()));
+        assert!(csp
+            .parallel_solutions(4)
+            .into_iter()
+            .map(Solution::from)
+            .eq(expected.clone()));
+    }
+}
+
+#[test]
+fn problem_file_first_solution() {
+    let problem =
+        Problem::from_file("book/chapter_03/problems/australian_map_colouring.json").unwrap();
+    let solution = Solution::from(problem.to_csp().unwrap().backtracking_search().unwrap());
+
+    // The same colouring as `australia_first_solution`, with the colours numbered.
+    assert_eq!(
+        solution.to_string(),
+        "New South Wales = 1\n\
+         Northern Territory = 1\n\
+         Queensland = 0\n\
+         South Australia = 2\n\
+         Tasmania = 1\n\
+         Victoria = 0\n\
+         Western Australia = 0"
+    );
+}
+
+#[test]
+fn cdcl_first_solution() {
+    let cnf = Cnf::from_file("book/chapter_03/problems/four_queens.cnf").unwrap();
+    let csp = cnf.to_csp().unwrap();
+
+    for _ in 0..10 {
+        let solution = csp.cdcl_search().unwrap();
+
+        assert_eq!(
+            cnf.solution_to_dimacs(&solution),
+            "v -1 -2 3 -4 5 -6 -7 -8 -9 -10 -11 12 -13 14 -15 -16 0"
+        );
+    }
+
+    let pigeonhole = Cnf::from_file("book/chapter_03/problems/pigeonhole_4_3.cnf").unwrap();
+
+    assert_eq!(pigeonhole.to_csp().unwrap().cdcl_search(), None);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use classic_computer_science_problems::csp::{Backtracking, Inference, Solution, VariableOrdering};
+
+use common::{australia, queens, Csp};
+
+/// Every solution of `csp` with each kind of backtracking, in a canonical order so they can be compared.
+fn solutions_by_backtracking(csp: &mut Csp<i32, i32>) -> Vec<Vec<Solution<i32, i32>>> {
+    [
+        Backtracking::Chronological,
+        Backtracking::ConflictDirected {
+            record_nogoods: false,
+        },
+        Backtracking::ConflictDirected {
+            record_nogoods: true,
+        },
+    ]
+    .into_iter()
+    .map(|backtracking| {
+        csp.backtracking = backtracking;
+
+        let mut solutions: Vec<Solution<i32, i32>> = csp.solutions().map(Solution::from).collect();
+
+        solutions.sort_by_key(|solution| solution.to_string());
+
+        solutions
+    })
+    .collect()
+}
+
+#[test]
+fn backjumping_finds_every_queens_solution() {
+    // Including no solution at all for three queens.
+    for (n, count) in [(3, 0), (4, 2), (5, 10), (6, 4), (7, 40), (8, 92)] {
+        for variable_ordering in [
+            VariableOrdering::FirstUnassigned,
+            VariableOrdering::MinimumRemainingValues,
+        ] {
+            for inference in [Inference::None, Inference::ForwardChecking] {
+                let mut csp = queens(n);
+
+                csp.variable_ordering = variable_ordering;
+                csp.inference = inference;
+
+                let found = solutions_by_backtracking(&mut csp);
+
+                assert_eq!(found[0].len(), count, "{n} queens");
+                assert_eq!(found[0], found[1], "{n} queens, without nogoods");
+                assert_eq!(found[0], found[2], "{n} queens, with nogoods");
+            }
+        }
+    }
+}
+
+#[test]
+fn backjumping_agrees_on_the_first_solution() {
+    let csp = australia();
+    let (expected, count) = (csp.backtracking_search(), csp.count_solutions());
+
+    for record_nogoods in [false, true] {
+        let mut csp = australia();
+
+        csp.backtracking = Backtracking::ConflictDirected { record_nogoods };
+
+        assert_eq!(csp.backtracking_search(), expected);
+        assert_eq!(csp.count_solutions(), count);
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use classic_computer_science_problems::csp::{
+    Constraint, CspError, Problem, ProblemError, Relation, StandardConstraint, CSP,
+};
+
+type Standard = StandardConstraint<&'static str, i64>;
+
+fn assignment(values: &[(&'static str, i64)]) -> HashMap<&'static str, i64> {
+    values.iter().copied().collect()
+}
+
+#[test]
+fn all_different_and_not_equal() {
+    let all_different = Standard::all_different(vec!["A", "B", "C"]);
+
+    assert!(all_different.is_satisfied(&assignment(&[("A", 1), ("B", 2)])));
+    assert!(!all_different.is_satisfied(&assignment(&[("A", 1), ("C", 1)])));
+    assert!(all_different.is_satisfied(&assignment(&[("A", 1), ("B", 2), ("C", 3)])));
+
+    let not_equal = Standard::not_equal("A", "B");
+
+    assert!(not_equal.is_satisfied(&assignment(&[("A", 1)])));
+    assert!(!not_equal.is_satisfied(&assignment(&[("A", 1), ("B", 1)])));
+    assert!(not_equal.is_satisfied(&assignment(&[("A", 1), ("B", 2)])));
+}
+
+#[test]
+fn predicate_waits_for_both_variables() {
+    let less = Standard::predicate("A", "B", |a, b| a < b);
+
+    assert!(less.is_satisfied(&assignment(&[("A", 5)])));
+    assert!(less.is_satisfied(&assignment(&[("A", 1), ("B", 2)])));
+    assert!(!less.is_satisfied(&assignment(&[("A", 2), ("B", 1)])));
+}
+
+#[test]
+fn linear_relations() {
+    use Relation::*;
+
+    // 2 A - B against 3, with A = 2 and B = 1.
+    for (relation, holds) in [
+        (Equal, true),
+        (NotEqual, false),
+        (Less, false),
+        (LessOrEqual, true),
+        (Greater, false),
+        (GreaterOrEqual, true),
+    ] {
+        let constraint = Standard::linear(vec![(2, "A"), (-1, "B")], relation, 3);
+
+        assert_eq!(
+            constraint.is_satisfied(&assignment(&[("A", 2), ("B", 1)])),
+            holds,
+            "{relation:?}"
+        );
+        assert!(constraint.is_satisfied(&assignment(&[("A", 2)])));
+    }
+
+    // A variable in several terms has its coefficients added up: A + A - A = A.
+    let repeated = Standard::linear(vec![(1, "A"), (1, "A"), (-1, "A")], Equal, 4);
+
+    assert!(repeated.is_satisfied(&assignment(&[("A", 4)])));
+    assert!(!repeated.is_satisfied(&assignment(&[("A", 8)])));
+}
+
+#[test]
+fn linear_does_not_overflow() {
+    let max = i64::MAX;
+
+    // Each term fits in an `i64`, but their sum does not.
+    let sum = Standard::linear(vec![(max, "A"), (max, "B")], Relation::Greater, max);
+
+    assert!(sum.is_satisfied(&assignment(&[("A", 1), ("B", 1)])));
+    assert!(!sum.is_satisfied(&assignment(&[("A", 1), ("B", 0)])));
+
+    // Neither does `i64::MAX * 2`, which would wrap around to -2.
+    let product = Standard::linear(vec![(max, "A")], Relation::Equal, -2);
+
+    assert!(!product.is_satisfied(&assignment(&[("A", 2)])));
+
+    let problem = Problem::from_text("var A in 0..3\n9223372036854775807 A = 1").unwrap();
+
+    assert_eq!(problem.to_csp().unwrap().backtracking_search(), None);
+}
+
+#[test]
+fn table_needs_a_matching_tuple() {
+    let table = Standard::table(vec!["A", "B"], vec![vec![0, 1], vec![2, 3]]);
+
+    assert!(table.is_satisfied(&assignment(&[("A", 2)])));
+    assert!(!table.is_satisfied(&assignment(&[("A", 1)])));
+    assert!(table.is_satisfied(&assignment(&[("A", 0), ("B", 1)])));
+    assert!(!table.is_satisfied(&assignment(&[("A", 0), ("B", 3)])));
+    assert!(table.validate().is_ok());
+}
+
+#[test]
+fn table_rejects_the_wrong_tuple_length() {
+    let domains = ["A", "B"]
+        .iter()
+        .map(|&variable| (variable, vec![0, 1, 2]))
+        .collect();
+    let mut csp = CSP::new(vec!["A", "B"], domains).unwrap();
+
+    assert_eq!(
+        csp.add_constraint(Standard::table(
+            vec!["A", "B"],
+            vec![vec![0, 1], vec![1, 2], vec![2]]
+        )),
+        Err(CspError::TupleLength {
+            tuple: 2,
+            expected: 2,
+            found: 1
+        })
+    );
+
+    let problem = Problem::from_text("var A B in 0..2\ntable A B: (0, 1), (2)").unwrap();
+
+    assert!(matches!(
+        problem.to_csp(),
+        Err(ProblemError::Csp(CspError::TupleLength {
+            tuple: 1,
+            expected: 2,
+            found: 1
+        }))
+    ));
+}
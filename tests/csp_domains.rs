@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use classic_computer_science_problems::csp::{
+    BitSetDomain, Domain, DomainRepresentation, Inference, IntervalDomain, Problem, ValueOrdering,
+    ValuesDomain, VariableOrdering,
+};
+
+/// Remove values at random and restore them in the reverse order, as the search does,
+/// checking `domain` against the values that should be live after every step.
+/// Values that are the same integer are removed and restored together.
+fn check_against_model(domain: &mut dyn Domain, values: &[i64], rng: &mut StdRng) {
+    let mut removed: Vec<usize> = Vec::new();
+
+    for _ in 0..200 {
+        if removed.is_empty() || (removed.len() < values.len() && rng.gen_bool(0.6)) {
+            let live: Vec<usize> = (0..values.len())
+                .filter(|&index| domain.contains(index))
+                .collect();
+
+            if let Some(&index) = live.get(rng.gen_range(0..live.len().max(1))) {
+                domain.remove(index);
+                removed.push(index);
+            }
+        } else {
+            domain.restore(removed.pop().unwrap());
+        }
+
+        let mut live: Vec<i64> = values
+            .iter()
+            .filter(|value| !removed.iter().any(|&index| values[index] == **value))
+            .copied()
+            .collect();
+
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(domain.contains(index), live.contains(value), "{values:?}");
+        }
+
+        live.sort_unstable();
+        live.dedup();
+
+        assert_eq!(domain.size(), live.len(), "{values:?}");
+    }
+}
+
+#[test]
+fn domains_follow_removals_and_restores() {
+    let mut rng = StdRng::seed_from_u64(16);
+
+    for values in [
+        vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        vec![7, -3, 2, 9, 4, -1, 0],
+        vec![5, 5, 1, 2, 2, 3],
+        vec![i64::MIN, i64::MIN + 1, 0, i64::MAX - 1, i64::MAX],
+    ] {
+        let mut domain = IntervalDomain::new(values.clone());
+
+        check_against_model(&mut domain, &values, &mut rng);
+
+        if let Some(mut domain) = BitSetDomain::new(values.clone()) {
+            check_against_model(&mut domain, &values, &mut rng);
+        }
+    }
+
+    // Every value here is different, which the values domain needs to agree with the model.
+    let mut domain = ValuesDomain::new(10);
+
+    check_against_model(&mut domain, &(0..10).collect::<Vec<_>>(), &mut rng);
+}
+
+#[test]
+fn sparse_and_wide_bit_sets_are_refused() {
+    assert!(BitSetDomain::new(vec![0, 1_000_000_000]).is_none());
+    assert!(BitSetDomain::new(vec![i64::MIN, i64::MAX]).is_none());
+    assert!(BitSetDomain::new(vec![-5, 100]).is_some());
+
+    // The search falls back to intervals for these.
+    let domain = DomainRepresentation::<i64>::bit_set().domain(&[i64::MIN, 0, i64::MAX]);
+
+    assert_eq!(domain.size(), 3);
+    assert!((0..3).all(|index| domain.contains(index)));
+}
+
+#[test]
+fn bit_set_counts_duplicates_once() {
+    let mut domain = BitSetDomain::new(vec![3, 3, 4]).unwrap();
+
+    assert_eq!(domain.size(), 2);
+
+    domain.remove(0);
+
+    assert!(!domain.contains(1));
+    assert_eq!(domain.size(), 1);
+
+    domain.restore(0);
+
+    assert_eq!(domain.size(), 2);
+}
+
+/// Eight queens in the text format, with the queen of column `n` in row `Qn`.
+fn eight_queens() -> String {
+    let mut text = String::from(
+        "var Q1 Q2 Q3 Q4 Q5 Q6 Q7 Q8 in 1..8\nall_different Q1 Q2 Q3 Q4 Q5 Q6 Q7 Q8\n",
+    );
+
+    for first in 1..=8 {
+        for second in first + 1..=8 {
+            let distance = second - first;
+
+            text += &format!(
+                "Q{first} - Q{second} != {distance}\nQ{first} - Q{second} != -{distance}\n"
+            );
+        }
+    }
+
+    text
+}
+
+#[test]
+fn representations_find_the_same_solutions() {
+    for text in [
+        eight_queens(),
+        // Wide and sparse domains, pruned by linear constraints.
+        "var A B in {-9223372036854775807, -5, 0, 3, 9223372036854775807}\n\
+         var C in {0, 1000000000, 2000000000}\n\
+         A + B <= 3\n\
+         A != B\n\
+         C - 1000000000 A >= 0"
+            .to_string(),
+        // No solution at all.
+        "var A B C in 0..1\nall_different A B C".to_string(),
+    ] {
+        let mut csp = Problem::from_text(&text).unwrap().to_csp().unwrap();
+
+        csp.variable_ordering = VariableOrdering::MinimumRemainingValues;
+        csp.value_ordering = ValueOrdering::DomainOrder;
+        csp.inference = Inference::ForwardChecking;
+
+        let mut found: Vec<(usize, Option<HashMap<String, i64>>)> = Vec::new();
+
+        for representation in [
+            DomainRepresentation::Values,
+            DomainRepresentation::bit_set(),
+            DomainRepresentation::interval(),
+        ] {
+            csp.domain_representation = representation;
+
+            found.push((csp.count_solutions(), csp.backtracking_search()));
+        }
+
+        assert_eq!(found[0], found[1], "{text}");
+        assert_eq!(found[0], found[2], "{text}");
+    }
+}
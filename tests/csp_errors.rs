@@ -0,0 +1,157 @@
+mod common;
+
+use std::collections::HashMap;
+
+use classic_computer_science_problems::csp::{
+    Constraint, CspError, Solution, StandardConstraint, CSP,
+};
+
+use common::{australia, Csp};
+
+type Standard = StandardConstraint<&'static str, &'static str>;
+
+fn domains(variables: &[&'static str]) -> HashMap<&'static str, Vec<i32>> {
+    variables
+        .iter()
+        .map(|&variable| (variable, vec![0, 1]))
+        .collect()
+}
+
+#[test]
+fn new_rejects_bad_variables_and_domains() {
+    let new = |variables: Vec<&'static str>, domains| -> Result<Csp<&str, i32>, _> {
+        CSP::new(variables, domains)
+    };
+
+    assert_eq!(
+        new(vec!["A", "B"], domains(&["A"])).unwrap_err(),
+        CspError::MissingDomain("B")
+    );
+
+    let mut empty = domains(&["A", "B"]);
+
+    empty.insert("B", vec![]);
+
+    assert_eq!(
+        new(vec!["A", "B"], empty).unwrap_err(),
+        CspError::EmptyDomain("B")
+    );
+    assert_eq!(
+        new(vec!["A", "B", "A"], domains(&["A", "B"])).unwrap_err(),
+        CspError::DuplicateVariable("A")
+    );
+    assert!(new(vec!["A", "B"], domains(&["A", "B"])).is_ok());
+}
+
+/// `region` has a different colour from each of its `neighbours`, and claims to involve `expected` regions in all.
+struct Neighbours {
+    region: &'static str,
+    neighbours: Vec<&'static str>,
+    expected: usize,
+}
+
+impl Constraint<&'static str, &'static str> for Neighbours {
+    fn variables(&self) -> Vec<&'static str> {
+        let mut variables = vec![self.region];
+
+        variables.extend(&self.neighbours);
+        variables
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<&'static str, &'static str>) -> bool {
+        let colour = assignment.get(self.region);
+
+        self.neighbours
+            .iter()
+            .all(|neighbour| colour.is_none() || assignment.get(neighbour) != colour)
+    }
+
+    fn arity(&self) -> Option<usize> {
+        Some(self.expected)
+    }
+}
+
+/// The variables of each constraint, under each variable it is registered under.
+fn filed(csp: &Csp<&'static str, &'static str>) -> Vec<(&'static str, Vec<Vec<&'static str>>)> {
+    let mut filed: Vec<_> = csp
+        .constraints
+        .iter()
+        .map(|(&variable, constraints)| {
+            (
+                variable,
+                constraints.iter().map(Constraint::variables).collect(),
+            )
+        })
+        .collect();
+
+    filed.sort();
+    filed
+}
+
+fn solutions(csp: &Csp<&'static str, &'static str>) -> Vec<Solution<&'static str, &'static str>> {
+    let mut solutions: Vec<_> = csp.solutions().map(Solution::from).collect();
+
+    solutions.sort_by_key(|solution| solution.to_string());
+    solutions
+}
+
+#[test]
+fn add_constraint_rejects_bad_constraints_and_leaves_the_csp_as_it_was() {
+    let mut csp = australia();
+    let (before, expected) = (filed(&csp), solutions(&csp));
+
+    // A constraint that would make Western Australia blue, if it got in.
+    let blue = |variables: Vec<&'static str>| {
+        let mut tuple = vec!["red"; variables.len()];
+
+        tuple[0] = "blue";
+
+        Standard::table(variables, vec![tuple])
+    };
+
+    for (constraint, error) in [
+        (
+            blue(vec!["Western Australia", "Atlantis"]),
+            CspError::UnknownVariable("Atlantis"),
+        ),
+        (
+            blue(vec!["Western Australia", "Tasmania", "Western Australia"]),
+            CspError::RepeatedConstraintVariable("Western Australia"),
+        ),
+        (
+            Standard::table(vec![], vec![vec![]]),
+            CspError::EmptyConstraint,
+        ),
+        (
+            Standard::custom(Neighbours {
+                region: "Western Australia",
+                neighbours: vec!["Northern Territory", "South Australia"],
+                expected: 2,
+            }),
+            CspError::ArityMismatch {
+                expected: 2,
+                found: 3,
+            },
+        ),
+    ] {
+        assert_eq!(csp.add_constraint(constraint), Err(error.clone()));
+        assert_eq!(filed(&csp), before, "{error}");
+        assert_eq!(solutions(&csp), expected, "{error}");
+    }
+
+    // The same constraints are accepted once they are well formed, and then they do change the CSP.
+    csp.add_constraint(blue(vec!["Western Australia", "Tasmania"]))
+        .unwrap();
+    csp.add_constraint(Standard::custom(Neighbours {
+        region: "Western Australia",
+        neighbours: vec!["Northern Territory", "South Australia"],
+        expected: 3,
+    }))
+    .unwrap();
+
+    assert_ne!(filed(&csp), before);
+    assert!(!solutions(&csp).is_empty());
+    assert!(csp
+        .solutions()
+        .all(|solution| solution["Western Australia"] == "blue"));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use classic_computer_science_problems::csp::{Backtracking, Constraint, StandardConstraint, CSP};
+
+use common::{australia, Csp};
+
+/// Australia with only red and green, which cannot be coloured: it is full of triangles of regions that all border each other.
+fn two_coloured_australia() -> Csp<&'static str, &'static str> {
+    let mut csp = australia();
+
+    for domain in csp.domains.values_mut() {
+        domain.retain(|&colour| colour != "blue");
+    }
+
+    csp
+}
+
+/// The same variables and domains as `csp`, under `constraints` alone.
+fn with_constraints(
+    csp: &Csp<&'static str, &'static str>,
+    constraints: &[StandardConstraint<&'static str, &'static str>],
+) -> Csp<&'static str, &'static str> {
+    let mut subset = CSP::new(csp.variables.clone(), csp.domains.clone()).unwrap();
+
+    for constraint in constraints {
+        subset.add_constraint(constraint.clone()).unwrap();
+    }
+
+    subset
+}
+
+#[test]
+fn conflict_is_unsatisfiable_and_minimal() {
+    for backtracking in [
+        Backtracking::Chronological,
+        Backtracking::ConflictDirected {
+            record_nogoods: true,
+        },
+    ] {
+        let mut csp = two_coloured_australia();
+
+        csp.backtracking = backtracking;
+
+        let conflict = csp.minimal_unsatisfiable_subset().unwrap();
+
+        // Any triangle of regions will do, and no fewer constraints can rule out two colours.
+        assert_eq!(conflict.len(), 3);
+        assert_eq!(
+            with_constraints(&csp, &conflict).backtracking_search(),
+            None
+        );
+
+        for dropped in 0..conflict.len() {
+            let mut rest = conflict.clone();
+
+            rest.remove(dropped);
+
+            assert!(
+                with_constraints(&csp, &rest)
+                    .backtracking_search()
+                    .is_some(),
+                "still unsatisfiable without constraint {dropped}"
+            );
+        }
+    }
+}
+
+#[test]
+fn no_conflict_when_there_is_a_solution() {
+    assert!(australia().minimal_unsatisfiable_subset().is_none());
+}
+
+#[test]
+fn conflict_leaves_out_constraints_that_do_not_matter() {
+    // Two colours for a ring of five regions, which is odd, with a chain of three more regions hanging off it.
+    let ring = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)];
+    let chain = [(4, 5), (5, 6), (6, 7)];
+
+    let variables: Vec<i32> = (0..8).collect();
+    let domains = variables
+        .iter()
+        .map(|&variable| (variable, vec![0, 1]))
+        .collect();
+    let mut csp: Csp<i32, i32> = CSP::new(variables, domains).unwrap();
+
+    for (first, second) in chain.into_iter().chain(ring) {
+        csp.add_constraint(StandardConstraint::not_equal(first, second))
+            .unwrap();
+    }
+
+    let mut conflict: Vec<Vec<i32>> = csp
+        .minimal_unsatisfiable_subset()
+        .unwrap()
+        .iter()
+        .map(|constraint| {
+            let mut variables = constraint.variables();
+
+            variables.sort();
+            variables
+        })
+        .collect();
+
+    conflict.sort();
+
+    assert_eq!(
+        conflict,
+        [[0, 1], [0, 4], [1, 2], [2, 3], [3, 4]].map(Vec::from)
+    );
+}
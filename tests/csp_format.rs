@@ -0,0 +1,165 @@
+use classic_computer_science_problems::csp::{
+    ConstraintSpec, DomainSpec, Problem, ProblemError, Relation, Solution,
+};
+
+#[test]
+fn huge_ranges_are_rejected() {
+    for text in [
+        "var A in 0..10000000000",
+        "var A in -9223372036854775807..9223372036854775807",
+    ] {
+        let problem = Problem::from_text(text).unwrap();
+
+        assert!(matches!(problem.to_csp(), Err(ProblemError::Invalid(_))));
+    }
+
+    let largest = DomainSpec::Range {
+        min: 1,
+        max: DomainSpec::MAX_RANGE_SIZE as i64,
+    };
+
+    assert_eq!(
+        largest.values().unwrap().len() as u64,
+        DomainSpec::MAX_RANGE_SIZE
+    );
+}
+
+fn linear(terms: &[(i64, &str)], relation: Relation, constant: i64) -> ConstraintSpec {
+    ConstraintSpec::Linear {
+        terms: terms
+            .iter()
+            .map(|&(coefficient, variable)| (coefficient, variable.to_string()))
+            .collect(),
+        relation,
+        constant,
+    }
+}
+
+/// The constraints of a problem with variables `A`, `B` and `C` and the given line.
+fn constraints(line: &str) -> Vec<ConstraintSpec> {
+    Problem::from_text(&format!("var A B C in 0..9\n{line}"))
+        .unwrap()
+        .constraints
+}
+
+#[test]
+fn variable_statements() {
+    let problem = Problem::from_text(
+        "var A in 1..3\n\
+         var B C in {-1, 0, 5}\n\
+         var D in -2 .. -1",
+    )
+    .unwrap();
+
+    let domains: Vec<(&str, DomainSpec)> = problem
+        .variables
+        .iter()
+        .map(|variable| (variable.name.as_str(), variable.domain.clone()))
+        .collect();
+
+    assert_eq!(
+        domains,
+        [
+            ("A", DomainSpec::Range { min: 1, max: 3 }),
+            ("B", DomainSpec::Values(vec![-1, 0, 5])),
+            ("C", DomainSpec::Values(vec![-1, 0, 5])),
+            ("D", DomainSpec::Range { min: -2, max: -1 }),
+        ]
+    );
+}
+
+#[test]
+fn linear_statements() {
+    use Relation::*;
+
+    for (line, expected) in [
+        (
+            "A + 2 B <= 7",
+            linear(&[(1, "A"), (2, "B")], LessOrEqual, 7),
+        ),
+        (
+            "3 * A - B = C",
+            linear(&[(3, "A"), (-1, "B"), (-1, "C")], Equal, 0),
+        ),
+        ("-A + 4 > -2 C", linear(&[(-1, "A"), (2, "C")], Greater, -4)),
+        ("A * 2 = 4", linear(&[(2, "A")], Equal, 4)),
+        ("A - -1 = B", linear(&[(1, "A"), (-1, "B")], Equal, -1)),
+        (
+            "A + -2 B != 1 - 3",
+            linear(&[(1, "A"), (-2, "B")], NotEqual, -2),
+        ),
+        ("A >= B", linear(&[(1, "A"), (-1, "B")], GreaterOrEqual, 0)),
+        ("A < 5", linear(&[(1, "A")], Less, 5)),
+    ] {
+        assert_eq!(constraints(line), [expected], "{line}");
+    }
+}
+
+#[test]
+fn all_different_and_table_statements() {
+    assert_eq!(
+        constraints("all_different A B C"),
+        [ConstraintSpec::AllDifferent(vec![
+            "A".to_string(),
+            "B".to_string(),
+            "C".to_string()
+        ])]
+    );
+    assert_eq!(
+        constraints("table A B: (0, 1), (2, -3)"),
+        [ConstraintSpec::Table {
+            variables: vec!["A".to_string(), "B".to_string()],
+            tuples: vec![vec![0, 1], vec![2, -3]],
+        }]
+    );
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    let problem = Problem::from_text(
+        "# A comment on its own line\n\
+         \n\
+         var A B in 0..1   # and one after a statement\n\
+         \t# an indented one\n\
+         A != B#without a space",
+    )
+    .unwrap();
+
+    assert_eq!(problem.variables.len(), 2);
+    assert_eq!(
+        problem.constraints,
+        [linear(&[(1, "A"), (-1, "B")], Relation::NotEqual, 0)]
+    );
+}
+
+#[test]
+fn syntax_errors_give_their_line() {
+    for (text, line) in [
+        ("var A in 0..9\nA * B = 1", 2),
+        ("var A in 0..9\n\n# comment\nA = (1 + 2)", 4),
+        ("var A in 0..9 10", 1),
+        ("var A B in 0..9\nA + B = 1\nall_different", 3),
+        ("var A in 0..9\nA = 99999999999999999999", 2),
+        ("var A in 0..9\nA = 9223372036854775807 + 1", 2),
+    ] {
+        match Problem::from_text(text) {
+            Err(ProblemError::Syntax { line: found, .. }) => assert_eq!(found, line, "{text}"),
+            other => panic!("{text}: expected a syntax error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn text_problem_solves() {
+    let problem = Problem::from_text(
+        "var A B C in 1..3\n\
+         all_different A B C\n\
+         A * 2 = C + 1\n\
+         table A B: (2, 1), (2, 3)",
+    )
+    .unwrap();
+
+    let solution = Solution::from(problem.to_csp().unwrap().backtracking_search().unwrap());
+
+    assert_eq!(solution.to_string(), "A = 2\nB = 1\nC = 3");
+}
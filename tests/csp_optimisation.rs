@@ -0,0 +1,287 @@
+mod common;
+
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use classic_computer_science_problems::csp::{
+    Objective, OptimisationOutcome, Relation, SearchConfig, StandardConstraint, StopReason,
+    Symmetry, CSP,
+};
+
+use common::{australia, Csp};
+
+const VARIABLES: [char; 4] = ['A', 'B', 'C', 'D'];
+
+/// Four variables from 0 to 4, under a few random linear constraints, which may leave no solution at all.
+fn random_problem(rng: &mut StdRng) -> Csp<char, i64> {
+    let domains = VARIABLES
+        .iter()
+        .map(|&variable| (variable, (0..=4).collect()))
+        .collect();
+    let mut csp = CSP::new(VARIABLES.to_vec(), domains).unwrap();
+
+    for _ in 0..rng.gen_range(1..=4) {
+        let mut terms = Vec::new();
+
+        for variable in VARIABLES {
+            if rng.gen_bool(0.5) {
+                terms.push((rng.gen_range(-3..=3), variable));
+            }
+        }
+
+        if terms.is_empty() {
+            continue;
+        }
+
+        let relation = [
+            Relation::LessOrEqual,
+            Relation::GreaterOrEqual,
+            Relation::NotEqual,
+        ][rng.gen_range(0..3)];
+
+        csp.add_constraint(StandardConstraint::linear(
+            terms,
+            relation,
+            rng.gen_range(-4..=8),
+        ))
+        .unwrap();
+    }
+
+    csp
+}
+
+/// Every solution of `csp`, found by trying every combination of values rather than by searching.
+fn brute_force(csp: &Csp<char, i64>) -> Vec<HashMap<char, i64>> {
+    let mut assignments = vec![HashMap::new()];
+
+    for variable in &csp.variables {
+        assignments = assignments
+            .into_iter()
+            .flat_map(|assignment| {
+                csp.domains[variable].iter().map(move |&value| {
+                    let mut assignment = assignment.clone();
+
+                    assignment.insert(*variable, value);
+                    assignment
+                })
+            })
+            .collect();
+    }
+
+    assignments.retain(|assignment| csp.is_solution(assignment));
+    assignments
+}
+
+/// `sum(weight * variable)` with a lower bound: the weights of the variables assigned so far,
+/// plus the cheapest value for each of the others.
+struct WeightedSum(HashMap<char, i64>);
+
+impl Objective<char, i64> for WeightedSum {
+    type Cost = i64;
+
+    fn cost(&self, solution: &HashMap<char, i64>) -> i64 {
+        self.0
+            .iter()
+            .map(|(variable, weight)| weight * solution[variable])
+            .sum()
+    }
+
+    fn lower_bound(&self, partial: &HashMap<char, i64>) -> Option<i64> {
+        Some(
+            self.0
+                .iter()
+                .map(|(variable, &weight)| match partial.get(variable) {
+                    Some(value) => weight * value,
+                    None => weight.min(0) * 4,
+                })
+                .sum(),
+        )
+    }
+}
+
+#[test]
+fn minimise_finds_the_brute_force_optimum() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let (mut optimal, mut unsatisfiable) = (0, 0);
+
+    for _ in 0..200 {
+        let csp = random_problem(&mut rng);
+        let weights = WeightedSum(
+            VARIABLES
+                .iter()
+                .map(|&variable| (variable, rng.gen_range(-5..=5)))
+                .collect(),
+        );
+
+        let expected = brute_force(&csp)
+            .iter()
+            .map(|solution| weights.cost(solution))
+            .min();
+
+        // Once with the lower bound to prune with, and once with only the cost.
+        let without_bound = |solution: &HashMap<char, i64>| weights.cost(solution);
+
+        for outcome in [
+            csp.minimise(&weights, SearchConfig::new(), |_, _| {}),
+            csp.minimise(&without_bound, SearchConfig::new(), |_, _| {}),
+        ] {
+            match (outcome, expected) {
+                (OptimisationOutcome::Optimal { solution, cost }, Some(expected)) => {
+                    assert!(csp.is_solution(&solution));
+                    assert_eq!(weights.cost(&solution), cost);
+                    assert_eq!(cost, expected);
+
+                    optimal += 1;
+                }
+                (OptimisationOutcome::Unsatisfiable, None) => unsatisfiable += 1,
+                (outcome, expected) => panic!("expected {expected:?}, got {outcome:?}"),
+            }
+        }
+    }
+
+    assert!(optimal > 50 && unsatisfiable > 10);
+}
+
+#[test]
+fn minimise_reports_each_improvement() {
+    let csp = random_problem(&mut StdRng::seed_from_u64(1));
+    let objective = |solution: &HashMap<char, i64>| solution.values().sum::<i64>();
+
+    let mut improvements = Vec::new();
+    let outcome = csp.minimise(&objective, SearchConfig::new(), |_, &cost| {
+        improvements.push(cost)
+    });
+
+    assert!(improvements.windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(
+        matches!(outcome, OptimisationOutcome::Optimal { cost, .. } if Some(&cost) == improvements.last())
+    );
+}
+
+#[test]
+fn minimise_without_solutions() {
+    let domains = [('A', vec![0, 1]), ('B', vec![0, 1])].into_iter().collect();
+    let mut csp: Csp<char, i64> = CSP::new(vec!['A', 'B'], domains).unwrap();
+
+    csp.add_constraint(StandardConstraint::linear(
+        vec![(1, 'A'), (1, 'B')],
+        Relation::Equal,
+        3,
+    ))
+    .unwrap();
+
+    let objective = |solution: &HashMap<char, i64>| solution[&'A'];
+
+    assert_eq!(
+        csp.minimise(&objective, SearchConfig::new(), |_, _| {}),
+        OptimisationOutcome::Unsatisfiable
+    );
+    assert_eq!(
+        csp.minimise(&objective, SearchConfig::new().with_max_nodes(1), |_, _| {}),
+        OptimisationOutcome::GaveUp {
+            best: None,
+            reason: StopReason::NodeLimit
+        }
+    );
+}
+
+#[test]
+fn minimise_penalty_finds_the_brute_force_optimum() {
+    let mut rng = StdRng::seed_from_u64(10);
+    let (mut optimal, mut unsatisfiable) = (0, 0);
+
+    for _ in 0..200 {
+        let mut csp = random_problem(&mut rng);
+
+        for _ in 0..rng.gen_range(1..=5) {
+            // Either a value to prefer for one variable, or an order for two different ones.
+            let first = rng.gen_range(0..4);
+            let second = (first + rng.gen_range(1..4)) % 4;
+
+            let preference = if rng.gen_bool(0.5) {
+                StandardConstraint::linear(
+                    vec![(1, VARIABLES[first])],
+                    Relation::Equal,
+                    rng.gen_range(0..=4),
+                )
+            } else {
+                StandardConstraint::linear(
+                    vec![(1, VARIABLES[first]), (-1, VARIABLES[second])],
+                    Relation::Greater,
+                    0,
+                )
+            };
+
+            csp.add_soft_constraint(preference, rng.gen_range(1..=10))
+                .unwrap();
+        }
+
+        let expected = brute_force(&csp)
+            .iter()
+            .map(|solution| csp.penalty(solution))
+            .min();
+
+        match (
+            csp.minimise_penalty(SearchConfig::new(), |_, _| {}),
+            expected,
+        ) {
+            (OptimisationOutcome::Optimal { solution, cost }, Some(expected)) => {
+                assert!(csp.is_solution(&solution));
+                assert_eq!(csp.penalty(&solution), cost);
+                assert_eq!(cost, expected);
+
+                optimal += 1;
+            }
+            (OptimisationOutcome::Unsatisfiable, None) => unsatisfiable += 1,
+            (outcome, expected) => panic!("expected {expected:?}, got {outcome:?}"),
+        }
+    }
+
+    assert!(optimal > 50 && unsatisfiable > 10);
+}
+
+#[test]
+fn minimise_penalty_without_solutions() {
+    let domains = [('A', vec![0, 1]), ('B', vec![0, 1])].into_iter().collect();
+    let mut csp: Csp<char, i64> = CSP::new(vec!['A', 'B'], domains).unwrap();
+
+    csp.add_constraint(StandardConstraint::linear(
+        vec![(1, 'A'), (1, 'B')],
+        Relation::Equal,
+        3,
+    ))
+    .unwrap();
+    csp.add_soft_constraint(StandardConstraint::not_equal('A', 'B'), 1)
+        .unwrap();
+
+    assert_eq!(
+        csp.minimise_penalty(SearchConfig::new(), |_, _| {}),
+        OptimisationOutcome::Unsatisfiable
+    );
+}
+
+#[test]
+fn minimise_ignores_symmetries() {
+    // Breaking the symmetry keeps only the colourings where red comes before green and green before blue,
+    // none of which colour Western Australia, the first region, blue.
+    let mut csp = australia();
+
+    csp.add_symmetry(Symmetry::InterchangeableValues(vec![
+        "red", "green", "blue",
+    ]))
+    .unwrap();
+    csp.add_soft_constraint(
+        StandardConstraint::table(vec!["Western Australia"], vec![vec!["blue"]]),
+        5,
+    )
+    .unwrap();
+
+    match csp.minimise_penalty(SearchConfig::new(), |_, _| {}) {
+        OptimisationOutcome::Optimal { solution, cost } => {
+            assert_eq!(cost, 0);
+            assert_eq!(solution["Western Australia"], "blue");
+        }
+        outcome => panic!("expected an optimal solution, got {outcome:?}"),
+    }
+}
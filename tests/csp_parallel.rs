@@ -0,0 +1,41 @@
+mod common;
+
+use classic_computer_science_problems::csp::Inference;
+
+use common::{australia, queens};
+
+#[test]
+fn parallel_count_matches_sequential() {
+    // Three queens have no solution. Fifty threads are far more than the eight values of the first queen.
+    for n in 3..=8 {
+        let mut csp = queens(n);
+
+        for inference in [Inference::None, Inference::ForwardChecking] {
+            csp.inference = inference;
+
+            let count = csp.count_solutions();
+
+            for threads in [1, 2, 3, 8, 50] {
+                assert_eq!(
+                    csp.parallel_count_solutions(threads),
+                    count,
+                    "{n} queens on {threads} threads"
+                );
+                assert_eq!(csp.parallel_solutions(threads).len(), count);
+            }
+        }
+    }
+}
+
+#[test]
+fn parallel_search_finds_a_solution() {
+    let csp = australia();
+
+    for threads in [1, 4, 50] {
+        let solution = csp.parallel_backtracking_search(threads).unwrap();
+
+        assert!(csp.is_solution(&solution), "{threads} threads");
+    }
+
+    assert_eq!(queens(3).parallel_backtracking_search(50), None);
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use classic_computer_science_problems::csp::{
+    Constraint, PcpError, Relation, StandardConstraint, CSP,
+};
+
+type Csp = CSP<char, i64, StandardConstraint<char, i64>>;
+
+const VARIABLES: [char; 3] = ['A', 'B', 'C'];
+
+fn csp(domain: &[i64]) -> Csp {
+    let domains = VARIABLES
+        .iter()
+        .map(|&variable| (variable, domain.to_vec()))
+        .collect();
+
+    CSP::new(VARIABLES.to_vec(), domains).unwrap()
+}
+
+/// libpcp finds a solution exactly when the backtracking search does, and any solution it finds is valid.
+fn assert_agrees(csp: &Csp) {
+    let expected = csp.backtracking_search();
+
+    match csp.pcp_search().unwrap() {
+        Some(solution) => {
+            assert!(expected.is_some(), "{csp:?}: libpcp found {solution:?}");
+            assert!(csp.is_solution(&solution), "{csp:?}: {solution:?}");
+        }
+        None => assert_eq!(expected, None, "{csp:?}"),
+    }
+}
+
+#[test]
+fn linear_constraints_agree_with_backtracking() {
+    use Relation::*;
+
+    let mut rng = StdRng::seed_from_u64(17);
+    let (mut satisfiable, mut unsatisfiable) = (0, 0);
+
+    for relation in [Equal, NotEqual, Less, LessOrEqual, Greater, GreaterOrEqual] {
+        for _ in 0..40 {
+            let mut csp = csp(&[-2, 0, 1, 3]);
+
+            // Coefficients of either sign, not only 1 and -1, on one to three variables.
+            for _ in 0..rng.gen_range(1..=3) {
+                let terms = VARIABLES
+                    .iter()
+                    .take(rng.gen_range(1..=3))
+                    .map(|&variable| (rng.gen_range(-3..=3), variable))
+                    .collect();
+
+                csp.add_constraint(StandardConstraint::linear(
+                    terms,
+                    relation,
+                    rng.gen_range(-6..=6),
+                ))
+                .unwrap();
+            }
+
+            assert_agrees(&csp);
+
+            if csp.backtracking_search().is_some() {
+                satisfiable += 1;
+            } else {
+                unsatisfiable += 1;
+            }
+        }
+    }
+
+    // Either answer would be easy to get right on its own.
+    assert!(
+        satisfiable > 50 && unsatisfiable > 20,
+        "{satisfiable}, {unsatisfiable}"
+    );
+}
+
+#[test]
+fn table_and_all_different_agree_with_backtracking() {
+    let mut table = csp(&[0, 1, 2]);
+
+    table
+        .add_constraint(StandardConstraint::table(
+            vec!['A', 'B'],
+            vec![vec![0, 2], vec![2, 1]],
+        ))
+        .unwrap();
+    table
+        .add_constraint(StandardConstraint::linear(
+            vec![(1, 'A'), (-1, 'C')],
+            Relation::Equal,
+            0,
+        ))
+        .unwrap();
+
+    assert_agrees(&table);
+    assert!(table.pcp_search().unwrap().is_some());
+
+    let mut empty = csp(&[0, 1, 2]);
+
+    empty
+        .add_constraint(StandardConstraint::table(vec!['A', 'B'], vec![]))
+        .unwrap();
+
+    assert_eq!(empty.pcp_search().unwrap(), None);
+
+    for values in [&[0, 1, 2][..], &[0, 1]] {
+        let mut all_different = csp(values);
+
+        all_different
+            .add_constraint(StandardConstraint::all_different(VARIABLES.to_vec()))
+            .unwrap();
+
+        assert_agrees(&all_different);
+        assert_eq!(
+            all_different.pcp_search().unwrap().is_some(),
+            values.len() == 3
+        );
+    }
+}
+
+#[test]
+fn integers_beyond_i32_are_out_of_range() {
+    let wide = csp(&[0, i64::from(i32::MAX) + 1]);
+
+    assert_eq!(wide.pcp_search(), Err(PcpError::OutOfRange(vec!['A'])));
+
+    // Every value fits, but a coefficient times a value does not.
+    let mut product = csp(&[0, 1, 2]);
+
+    product
+        .add_constraint(StandardConstraint::linear(
+            vec![(i64::from(i32::MAX), 'B')],
+            Relation::Equal,
+            0,
+        ))
+        .unwrap();
+
+    assert_eq!(product.pcp_search(), Err(PcpError::OutOfRange(vec!['B'])));
+
+    let mut constant = csp(&[0, 1, 2]);
+
+    constant
+        .add_constraint(StandardConstraint::linear(
+            vec![(1, 'A'), (1, 'C')],
+            Relation::LessOrEqual,
+            i64::from(i32::MAX),
+        ))
+        .unwrap();
+
+    assert_eq!(
+        constant.pcp_search(),
+        Err(PcpError::OutOfRange(vec!['A', 'C']))
+    );
+}
+
+/// `A` is even, which only Rust code can check.
+struct Even;
+
+impl Constraint<char, i64> for Even {
+    fn variables(&self) -> Vec<char> {
+        vec!['A']
+    }
+
+    fn is_satisfied(&self, assignment: &HashMap<char, i64>) -> bool {
+        assignment.get(&'A').is_none_or(|value| value % 2 == 0)
+    }
+}
+
+#[test]
+fn rust_constraints_are_unsupported() {
+    let mut predicate = csp(&[0, 1, 2]);
+
+    predicate
+        .add_constraint(StandardConstraint::predicate('A', 'B', |a, b| a < b))
+        .unwrap();
+
+    assert_eq!(
+        predicate.pcp_search(),
+        Err(PcpError::UnsupportedConstraint(vec!['A', 'B']))
+    );
+
+    let mut custom = csp(&[0, 1, 2]);
+
+    custom
+        .add_constraint(StandardConstraint::custom(Even))
+        .unwrap();
+
+    assert_eq!(
+        custom.pcp_search(),
+        Err(PcpError::UnsupportedConstraint(vec!['A']))
+    );
+}
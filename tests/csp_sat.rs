@@ -0,0 +1,105 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use classic_computer_science_problems::csp::{Cnf, ProblemError};
+
+#[test]
+fn parses_dimacs() {
+    let cnf = Cnf::from_dimacs(
+        "c x1 or not x3, and x2 or x3 or not x1\n\
+         p cnf 3 2\n\
+         1 -3 0\n\
+         2 3\n\
+         -1 0\n",
+    )
+    .unwrap();
+
+    assert_eq!(cnf.variables, 3);
+    assert_eq!(cnf.clauses, [vec![1, -3], vec![2, 3, -1]]);
+}
+
+#[test]
+fn rejects_bad_literals() {
+    let error = Cnf::from_dimacs("p cnf 3 1\n1 x 0\n").unwrap_err();
+
+    assert!(matches!(error, ProblemError::Syntax { line: 2, .. }));
+}
+
+#[test]
+fn rejects_a_missing_zero() {
+    let error = Cnf::from_dimacs("p cnf 3 2\n1 -3 0\n2 3\n").unwrap_err();
+
+    assert!(matches!(error, ProblemError::Invalid(_)));
+}
+
+#[test]
+fn rejects_literals_beyond_the_header() {
+    let error = Cnf::from_dimacs("p cnf 3 1\n1 -4 0\n").unwrap_err();
+
+    assert!(matches!(error, ProblemError::Invalid(message) if message.contains("-4")));
+}
+
+#[test]
+fn accepts_a_wrong_clause_count() {
+    let cnf = Cnf::from_dimacs("p cnf 2 5\n1 2 0\n-1 0\n").unwrap();
+
+    assert_eq!(cnf.clauses.len(), 2);
+}
+
+#[test]
+fn empty_clause_is_unsatisfiable() {
+    for input in ["p cnf 2 2\n1 2 0\n0\n", "p cnf 0 1\n0\n"] {
+        let csp = Cnf::from_dimacs(input).unwrap().to_csp().unwrap();
+
+        assert_eq!(csp.cdcl_search(), None);
+        assert_eq!(csp.backtracking_search(), None);
+    }
+}
+
+/// A random formula with `clauses` clauses of one to three literals each.
+fn random_cnf(rng: &mut StdRng, variables: u32, clauses: usize) -> Cnf {
+    let clauses = (0..clauses)
+        .map(|_| {
+            (0..rng.gen_range(1..=3))
+                .map(|_| {
+                    let variable = i64::from(rng.gen_range(1..=variables));
+
+                    if rng.gen() {
+                        variable
+                    } else {
+                        -variable
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Cnf { variables, clauses }
+}
+
+#[test]
+fn cdcl_agrees_with_backtracking() {
+    let mut rng = StdRng::seed_from_u64(19);
+    let (mut satisfiable, mut unsatisfiable) = (0, 0);
+
+    for _ in 0..500 {
+        let variables = rng.gen_range(1..=8);
+        let clauses = rng.gen_range(1..=5 * variables as usize);
+        let cnf = random_cnf(&mut rng, variables, clauses);
+        let csp = cnf.to_csp().unwrap();
+
+        match (csp.cdcl_search(), csp.backtracking_search()) {
+            (Some(solution), Some(_)) => {
+                satisfiable += 1;
+
+                assert!(csp.is_solution(&solution), "{cnf:?}");
+            }
+            (None, None) => unsatisfiable += 1,
+            (cdcl, backtracking) => {
+                panic!("{cnf:?}: CDCL found {cdcl:?}, backtracking {backtracking:?}")
+            }
+        }
+    }
+
+    // Both kinds of formula come up, so both answers are checked.
+    assert!(satisfiable > 50 && unsatisfiable > 50);
+}
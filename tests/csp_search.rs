@@ -0,0 +1,206 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use classic_computer_science_problems::csp::{
+    CancellationToken, SearchConfig, SearchObserver, SearchOutcome, Solution, StandardConstraint,
+    StopReason, VariableOrdering, CSP,
+};
+
+use common::{australia, queens, Csp};
+
+#[test]
+fn limits_stop_the_search() {
+    let csp = queens(8);
+
+    assert_eq!(
+        csp.backtracking_search_with_config(SearchConfig::new().with_max_nodes(3)),
+        SearchOutcome::GaveUp(StopReason::NodeLimit)
+    );
+    assert_eq!(
+        csp.backtracking_search_with_config(SearchConfig::new().with_deadline(Instant::now())),
+        SearchOutcome::GaveUp(StopReason::Deadline)
+    );
+
+    let token = CancellationToken::new();
+
+    token.cancel();
+
+    assert_eq!(
+        csp.backtracking_search_with_config(SearchConfig::new().with_cancellation(token)),
+        SearchOutcome::GaveUp(StopReason::Cancelled)
+    );
+}
+
+#[test]
+fn generous_limits_do_not_stop_the_search() {
+    let generous = || {
+        SearchConfig::new()
+            .with_max_nodes(1_000_000)
+            .with_timeout(Duration::from_secs(60))
+            .with_cancellation(CancellationToken::new())
+    };
+
+    let csp = queens(8);
+
+    match csp.backtracking_search_with_config(generous()) {
+        SearchOutcome::Solved(solution) => {
+            assert_eq!(Some(solution.clone()), csp.backtracking_search());
+            assert!(csp.is_solution(&solution));
+        }
+        outcome => panic!("expected a solution, got {outcome:?}"),
+    }
+
+    // Three queens cannot be placed, and the search gets to explore every way of trying.
+    assert_eq!(
+        queens(3).backtracking_search_with_config(generous()),
+        SearchOutcome::Unsatisfiable
+    );
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Assign(&'static str, &'static str, usize),
+    Unassign(&'static str, usize),
+    Backtrack(&'static str, usize),
+    Solution,
+}
+
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+impl SearchObserver<&'static str, &'static str> for Recorder {
+    fn on_assign(&mut self, variable: &&'static str, value: &&'static str, depth: usize) {
+        self.0.push(Event::Assign(variable, value, depth));
+    }
+
+    fn on_unassign(&mut self, variable: &&'static str, depth: usize) {
+        self.0.push(Event::Unassign(variable, depth));
+    }
+
+    fn on_backtrack(&mut self, variable: &&'static str, depth: usize) {
+        self.0.push(Event::Backtrack(variable, depth));
+    }
+
+    fn on_solution(&mut self, _solution: &HashMap<&'static str, &'static str>) {
+        self.0.push(Event::Solution);
+    }
+}
+
+#[test]
+fn observer_sees_what_the_stats_count() {
+    // Australia is coloured without a single backtrack, so also colour it after ruling out red for Victoria.
+    let mut constrained = australia();
+
+    constrained
+        .add_constraint(StandardConstraint::table(
+            vec!["Victoria"],
+            vec![vec!["green"], vec!["blue"]],
+        ))
+        .unwrap();
+
+    for (csp, backtracks) in [(australia(), false), (constrained, true)] {
+        let mut recorder = Recorder::default();
+        let solution = csp.backtracking_search_with_observer(&mut recorder);
+        let (expected, stats) = csp.backtracking_search_with_stats();
+
+        assert_eq!(solution, expected);
+        assert_eq!(stats.backtracks > 0, backtracks);
+
+        // Each value taken back is the last one assigned and not yet taken back.
+        let mut assigned = Vec::new();
+
+        for event in &recorder.0 {
+            match *event {
+                Event::Assign(variable, _, depth) => {
+                    assigned.push(variable);
+                    assert_eq!(depth, assigned.len());
+                }
+                Event::Unassign(variable, depth) => {
+                    assert_eq!(depth, assigned.len());
+                    assert_eq!(assigned.pop(), Some(variable));
+                }
+                Event::Backtrack(..) | Event::Solution => {}
+            }
+        }
+
+        assert_eq!(assigned.len(), csp.variables.len());
+        assert_eq!(recorder.0.last(), Some(&Event::Solution));
+
+        let count = |matches: fn(&Event) -> bool| {
+            recorder.0.iter().filter(|event| matches(event)).count() as u64
+        };
+
+        assert_eq!(
+            count(|event| matches!(event, Event::Assign(..))),
+            stats.nodes
+        );
+        assert_eq!(
+            count(|event| matches!(event, Event::Backtrack(..))),
+            stats.backtracks
+        );
+        assert_eq!(count(|event| *event == Event::Solution), 1);
+    }
+}
+
+/// `A < B < C`, each of them from 1 to `max`.
+fn increasing(max: i32) -> Csp<char, i32> {
+    let domains = ['A', 'B', 'C']
+        .into_iter()
+        .map(|variable| (variable, (1..=max).collect()))
+        .collect();
+    let mut csp = CSP::new(vec!['A', 'B', 'C'], domains).unwrap();
+
+    csp.add_constraint(StandardConstraint::predicate('A', 'B', |a, b| a < b))
+        .unwrap();
+    csp.add_constraint(StandardConstraint::predicate('B', 'C', |b, c| b < c))
+        .unwrap();
+
+    csp
+}
+
+#[test]
+fn arc_consistency_narrows_the_domains() {
+    let mut csp = increasing(3);
+
+    assert!(csp.make_arc_consistent());
+    assert_eq!(csp.domains[&'A'], [1]);
+    assert_eq!(csp.domains[&'B'], [2]);
+    assert_eq!(csp.domains[&'C'], [3]);
+
+    // Two values cannot be put in increasing order three times.
+    let mut csp = increasing(2);
+
+    assert!(!csp.make_arc_consistent());
+    assert!(csp.domains.values().any(Vec::is_empty));
+}
+
+#[test]
+fn minimum_remaining_values_visits_fewer_nodes() {
+    let in_order = queens(8);
+    let mut most_constrained = queens(8);
+
+    most_constrained.variable_ordering = VariableOrdering::MinimumRemainingValues;
+
+    let (first, in_order_stats) = in_order.backtracking_search_with_stats();
+    let (second, most_constrained_stats) = most_constrained.backtracking_search_with_stats();
+
+    assert!(in_order.is_solution(&first.unwrap()));
+    assert!(most_constrained.is_solution(&second.unwrap()));
+    assert!(
+        most_constrained_stats.nodes < in_order_stats.nodes,
+        "{most_constrained_stats} against {in_order_stats}"
+    );
+
+    // Only the order the search takes changes, not what it finds.
+    let sorted = |csp: &Csp<i32, i32>| {
+        let mut solutions: Vec<_> = csp.solutions().map(Solution::from).collect();
+
+        solutions.sort_by_key(|solution| solution.to_string());
+        solutions
+    };
+
+    assert_eq!(sorted(&in_order).len(), 92);
+    assert_eq!(sorted(&most_constrained), sorted(&in_order));
+}
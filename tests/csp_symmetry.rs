@@ -0,0 +1,170 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use classic_computer_science_problems::csp::{
+    Backtracking, CspError, Inference, Solution, Symmetry, ValueOrdering, VariableOrdering,
+};
+
+use common::{australia, queens, Csp};
+
+const N: i32 = 8;
+
+/// Reflect the board from left to right: the queen of each column moves to the opposite column.
+fn left_right(n: i32) -> Symmetry<i32, i32> {
+    Symmetry::Variables((1..=n).map(|column| (column, n + 1 - column)).collect())
+}
+
+/// Reflect the board from top to bottom: every queen moves to the opposite row.
+fn top_bottom(n: i32) -> Symmetry<i32, i32> {
+    Symmetry::Values((1..=n).map(|row| (row, n + 1 - row)).collect())
+}
+
+fn reflect_left_right(solution: &HashMap<i32, i32>) -> HashMap<i32, i32> {
+    solution
+        .iter()
+        .map(|(&column, &row)| (N + 1 - column, row))
+        .collect()
+}
+
+fn reflect_top_bottom(solution: &HashMap<i32, i32>) -> HashMap<i32, i32> {
+    solution
+        .iter()
+        .map(|(&column, &row)| (column, N + 1 - row))
+        .collect()
+}
+
+fn sorted(solutions: impl Iterator<Item = HashMap<i32, i32>>) -> Vec<Solution<i32, i32>> {
+    let mut solutions: Vec<Solution<i32, i32>> = solutions.map(Solution::from).collect();
+
+    solutions.sort_by_key(|solution| solution.to_string());
+    solutions
+}
+
+#[test]
+fn reflections_keep_one_solution_of_each_orbit() {
+    let all: Vec<HashMap<i32, i32>> = queens(N).solutions().collect();
+
+    assert_eq!(all.len(), 92);
+
+    let mut csp = queens(N);
+
+    csp.add_symmetry(left_right(N)).unwrap();
+
+    let kept: Vec<HashMap<i32, i32>> = csp.solutions().collect();
+
+    assert_eq!(kept.len(), 46);
+    assert!(kept.iter().all(|solution| csp.is_solution(solution)));
+
+    csp.add_symmetry(top_bottom(N)).unwrap();
+
+    let kept: Vec<HashMap<i32, i32>> = csp.solutions().collect();
+
+    assert_eq!(kept.len(), 35);
+    assert!(kept.iter().all(|solution| csp.is_solution(solution)));
+
+    // Every solution is one of those kept, after one or both reflections, so no orbit is lost.
+    let kept: HashSet<Solution<i32, i32>> = kept.into_iter().map(Solution::from).collect();
+
+    for solution in &all {
+        let images = [
+            solution.clone(),
+            reflect_left_right(solution),
+            reflect_top_bottom(solution),
+            reflect_top_bottom(&reflect_left_right(solution)),
+        ];
+
+        assert!(
+            images
+                .into_iter()
+                .any(|image| kept.contains(&Solution::from(image))),
+            "{solution:?}"
+        );
+    }
+}
+
+#[test]
+fn invalid_symmetries_are_rejected() {
+    let mut csp = queens(N);
+
+    // Two columns both go to column 2, and nothing goes to column 1.
+    assert_eq!(
+        csp.add_symmetry(Symmetry::Variables(vec![(1, 2), (2, 2)])),
+        Err(CspError::InvalidSymmetry)
+    );
+    assert_eq!(
+        csp.add_symmetry(Symmetry::Variables(vec![(1, 99), (99, 1)])),
+        Err(CspError::UnknownVariable(99))
+    );
+    assert_eq!(
+        csp.add_symmetry(Symmetry::Values(vec![(1, 99), (99, 1)])),
+        Err(CspError::InvalidSymmetry)
+    );
+    assert_eq!(
+        csp.add_symmetry(Symmetry::InterchangeableValues(vec![1, 2, 1])),
+        Err(CspError::InvalidSymmetry)
+    );
+    assert_eq!(
+        csp.add_symmetry(Symmetry::InterchangeableValues(vec![1, 99])),
+        Err(CspError::InvalidSymmetry)
+    );
+
+    assert!(csp.symmetries().is_empty());
+    assert_eq!(csp.count_solutions(), 92);
+}
+
+/// Every combination of settings that the solutions found should not depend on.
+fn configure(csp: &mut Csp<i32, i32>, setting: usize) {
+    csp.value_ordering = [
+        ValueOrdering::DomainOrder,
+        ValueOrdering::LeastConstrainingValue,
+    ][setting % 2];
+    csp.variable_ordering = [
+        VariableOrdering::FirstUnassigned,
+        VariableOrdering::MinimumRemainingValues,
+    ][setting / 2 % 2];
+    csp.inference = [Inference::None, Inference::MaintainArcConsistency][setting / 4 % 2];
+    csp.backtracking = [
+        Backtracking::Chronological,
+        Backtracking::ConflictDirected {
+            record_nogoods: true,
+        },
+    ][setting / 8 % 2];
+}
+
+#[test]
+fn settings_do_not_change_the_solutions() {
+    for symmetries in [
+        vec![],
+        vec![left_right(6)],
+        vec![left_right(6), top_bottom(6)],
+    ] {
+        let mut csp = queens(6);
+
+        for symmetry in symmetries {
+            csp.add_symmetry(symmetry).unwrap();
+        }
+
+        let expected = sorted(csp.solutions());
+
+        for setting in 0..16 {
+            configure(&mut csp, setting);
+
+            assert_eq!(sorted(csp.solutions()), expected, "setting {setting}");
+            assert_eq!(csp.parallel_count_solutions(4), expected.len());
+        }
+    }
+}
+
+#[test]
+fn least_constraining_value_finds_every_colouring() {
+    let mut csp = australia();
+    let count = csp.count_solutions();
+
+    csp.value_ordering = ValueOrdering::LeastConstrainingValue;
+
+    let first = csp.backtracking_search().unwrap();
+
+    assert!(csp.is_solution(&first));
+    assert_eq!(csp.count_solutions(), count);
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use classic_computer_science_problems::{
+    generic_search::{astar, astar_with_costs, bfs, dijkstra, dijkstra_all, ida_star, iddfs, Node},
+    maze::{Maze, MazeLocation},
+};
+use ordered_float::OrderedFloat;
+use proptest::{collection::vec, prelude::*};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// A weighted graph, as a list of `(from, to, cost)` edges.
+fn graph(edges: &[(char, char, f64)]) -> HashMap<char, Vec<(char, OrderedFloat<f64>)>> {
+    let mut graph = HashMap::<char, Vec<(char, OrderedFloat<f64>)>>::new();
+
+    for &(from, to, cost) in edges {
+        graph
+            .entry(from)
+            .or_default()
+            .push((to, OrderedFloat(cost)));
+    }
+
+    graph
+}
+
+#[test]
+fn cheapest_path_over_fewest_steps() {
+    let graph = graph(&[
+        ('A', 'B', 1.0),
+        ('B', 'E', 10.0),
+        ('A', 'C', 2.0),
+        ('C', 'D', 2.0),
+        ('D', 'E', 2.0),
+    ]);
+
+    let goal = astar_with_costs(
+        'A',
+        |state| state == 'E',
+        |state| graph.get(&state).cloned().unwrap_or_default(),
+        |_| OrderedFloat(0.0),
+    )
+    .unwrap();
+
+    assert_eq!(goal.to_path(), ['A', 'C', 'D', 'E']);
+    assert_eq!(goal.cost(), OrderedFloat(6.0));
+}
+
+#[test]
+fn reopens_nodes_reached_more_cheaply() {
+    // The heuristic is admissible but not consistent: it makes `C` look worse than it is,
+    // so `D` is first expanded by way of `B`, and has to be expanded again once `C` finds a cheaper way there.
+    let graph = graph(&[
+        ('A', 'B', 1.0),
+        ('A', 'C', 1.0),
+        ('B', 'D', 5.0),
+        ('C', 'D', 1.0),
+        ('D', 'E', 10.0),
+    ]);
+    let heuristic = |state| OrderedFloat(if state == 'C' { 11.0 } else { 0.0 });
+
+    let goal = astar_with_costs(
+        'A',
+        |state| state == 'E',
+        |state| graph.get(&state).cloned().unwrap_or_default(),
+        heuristic,
+    )
+    .unwrap();
+
+    assert_eq!(goal.to_path(), ['A', 'C', 'D', 'E']);
+    assert_eq!(goal.cost(), OrderedFloat(12.0));
+}
+
+#[test]
+fn unreachable_goal() {
+    let graph = graph(&[('A', 'B', 1.0), ('B', 'A', 1.0)]);
+
+    let goal = astar_with_costs(
+        'A',
+        |state| state == 'C',
+        |state| graph.get(&state).cloned().unwrap_or_default(),
+        |_| OrderedFloat(0.0),
+    );
+
+    assert!(goal.is_none());
+}
+
+#[test]
+fn dijkstra_to_every_state() {
+    let graph = graph(&[
+        ('A', 'B', 4.0),
+        ('A', 'C', 1.0),
+        ('C', 'B', 2.0),
+        ('B', 'D', 1.0),
+        ('E', 'A', 1.0),
+    ]);
+    let successors = |state| graph.get(&state).cloned().unwrap_or_default();
+
+    let goal = dijkstra('A', |state| state == 'D', successors).unwrap();
+
+    assert_eq!(goal.to_path(), ['A', 'C', 'B', 'D']);
+    assert_eq!(goal.cost(), OrderedFloat(4.0));
+
+    let paths = dijkstra_all('A', successors);
+
+    assert_eq!(
+        paths.distances,
+        HashMap::from([
+            ('A', OrderedFloat(0.0)),
+            ('B', OrderedFloat(3.0)),
+            ('C', OrderedFloat(1.0)),
+            ('D', OrderedFloat(4.0)),
+        ])
+    );
+    assert_eq!(
+        paths.predecessors,
+        HashMap::from([('B', 'C'), ('C', 'A'), ('D', 'B')])
+    );
+    assert_eq!(paths.path_to(&'D').unwrap(), ['A', 'C', 'B', 'D']);
+    assert_eq!(paths.path_to(&'A').unwrap(), ['A']);
+    assert_eq!(paths.path_to(&'E'), None);
+}
+
+#[test]
+fn iddfs_goes_one_move_deeper_each_iteration() {
+    let graph = graph(&[
+        ('A', 'B', 1.0),
+        ('B', 'C', 1.0),
+        ('C', 'D', 1.0),
+        ('A', 'D', 5.0),
+    ]);
+    let successors = |state| {
+        graph
+            .get(&state)
+            .into_iter()
+            .flatten()
+            .map(|&(succ, _)| succ)
+            .collect()
+    };
+
+    let found = iddfs('A', |state| state == 'D', successors, 10);
+
+    // The move straight to `D` is found on the second iteration, whatever it would cost `dijkstra`.
+    assert_eq!(found.solution.unwrap().to_path(), ['A', 'D']);
+    assert_eq!(found.iterations, 2);
+
+    let found = iddfs('A', |state| state == 'C', successors, 1);
+
+    assert!(found.solution.is_none());
+    assert_eq!(found.iterations, 2);
+    assert!(!found.exhausted);
+
+    let found = iddfs('A', |state| state == 'C', successors, 2);
+
+    assert_eq!(found.solution.unwrap().to_path(), ['A', 'B', 'C']);
+    assert_eq!(found.iterations, 3);
+}
+
+#[test]
+fn iddfs_stops_once_nothing_is_cut_off() {
+    let graph = graph(&[
+        ('A', 'B', 1.0),
+        ('B', 'A', 1.0),
+        ('B', 'C', 1.0),
+        ('C', 'A', 1.0),
+    ]);
+    let successors = |state| {
+        graph
+            .get(&state)
+            .into_iter()
+            .flatten()
+            .map(|&(succ, _)| succ)
+            .collect()
+    };
+
+    // Going round the cycle never reaches `D`, and the search has to notice that rather than go on up to the limit.
+    let found = iddfs('A', |state| state == 'D', successors, 1000);
+
+    assert!(found.solution.is_none());
+    assert_eq!(found.iterations, 3);
+    assert!(found.exhausted);
+}
+
+#[test]
+fn unit_costs_count_steps() {
+    // A 5 by 5 grid without obstacles, from one corner to the other.
+    let goal = astar(
+        (0, 0),
+        |state| state == (4, 4),
+        |(row, column): (i32, i32)| {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .into_iter()
+                .map(|(dr, dc)| (row + dr, column + dc))
+                .filter(|&(row, column)| (0..5).contains(&row) && (0..5).contains(&column))
+                .collect()
+        },
+        |(row, column)| OrderedFloat(f64::from((4 - row) + (4 - column))),
+    )
+    .unwrap();
+
+    assert_eq!(goal.to_path().len(), 9);
+    assert_eq!(goal.cost(), OrderedFloat(8.0));
+}
+
+#[test]
+fn node_holds_its_path() {
+    // Every number leads to its double and the next number, so the search goes through far more states than are on the path.
+    let goal = bfs(
+        1,
+        |state| state == 12,
+        |state: u32| vec![state * 2, state + 1],
+    )
+    .unwrap();
+
+    assert_eq!(goal.path(), [1, 2, 3, 6, 12]);
+    assert_eq!(goal.to_path(), goal.path());
+    assert_eq!(*goal.state(), 12);
+    assert_eq!(goal.cost(), OrderedFloat(4.0));
+    assert_eq!(goal.into_path(), [1, 2, 3, 6, 12]);
+}
+
+/// A random maze with the start and goal somewhere in it, which may well be blocked off from one another.
+fn maze() -> impl Strategy<Value = Maze> {
+    maze_up_to(19)
+}
+
+/// A random maze no more than `size` cells wide or high.
+fn maze_up_to(size: usize) -> impl Strategy<Value = Maze> {
+    (2..=size, 2..=size).prop_flat_map(|(rows, columns)| {
+        let location = move || {
+            (0..rows as i32, 0..columns as i32)
+                .prop_map(|(row, column)| MazeLocation { row, column })
+        };
+
+        (location(), location(), 0.0..0.5, any::<u64>()).prop_map(
+            move |(start, goal, sparseness, seed)| {
+                Maze::new_with_setup_and_rng(
+                    rows,
+                    columns,
+                    start,
+                    goal,
+                    sparseness,
+                    &mut StdRng::seed_from_u64(seed),
+                )
+            },
+        )
+    })
+}
+
+/// Whether each step of `path` is a move `maze` allows.
+fn is_path(maze: &Maze, path: &[MazeLocation]) -> bool {
+    path.first() == Some(&maze.start)
+        && path.last() == Some(&maze.goal)
+        && path
+            .windows(2)
+            .all(|step| maze.get_successors(step[0]).contains(&step[1]))
+}
+
+proptest! {
+    #[test]
+    fn astar_finds_paths_as_short_as_bfs(maze in maze()) {
+        let astar_path = astar(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            |loc| maze.get_successors(loc),
+            |loc| OrderedFloat(maze.distance_to_goal(loc).into()),
+        );
+        let bfs_path = bfs(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            |loc| maze.get_successors(loc),
+        );
+
+        match (astar_path, bfs_path) {
+            (Some(astar_path), Some(bfs_path)) => {
+                let path = Vec::from(astar_path.to_path());
+
+                prop_assert!(is_path(&maze, &path));
+                prop_assert_eq!(path.len(), bfs_path.to_path().len());
+                prop_assert_eq!(astar_path.cost(), OrderedFloat((path.len() - 1) as f64));
+            }
+            (None, None) => {}
+            (astar_path, bfs_path) => prop_assert!(
+                false,
+                "A* found {:?} but BFS found {:?}",
+                astar_path.map(|node| node.to_path()),
+                bfs_path.map(|node| node.to_path())
+            ),
+        }
+    }
+
+    #[test]
+    fn astar_with_costs_finds_cheapest_paths(
+        maze in maze(),
+        terrain in vec(1.0..10.0f64, 400),
+        discounts in vec(0.0..=1.0f64, 400),
+    ) {
+        // Moving onto a cell costs between 1 and 10, so the Manhattan distance still never overestimates.
+        // Scaling it down by a different amount on each cell keeps it admissible but not consistent, so nodes get reopened.
+        let cell = |loc: MazeLocation| loc.row as usize * 20 + loc.column as usize;
+        let successors = |loc| {
+            maze.get_successors(loc)
+                .into_iter()
+                .map(|succ| (succ, OrderedFloat(terrain[cell(succ)])))
+                .collect::<Vec<_>>()
+        };
+
+        let uniform = dijkstra(maze.start, |loc| maze.test_goal(loc), successors);
+        let consistent = astar_with_costs(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            successors,
+            |loc| OrderedFloat(maze.distance_to_goal(loc).into()),
+        );
+        let inconsistent = astar_with_costs(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            successors,
+            |loc| OrderedFloat(f64::from(maze.distance_to_goal(loc)) * discounts[cell(loc)]),
+        );
+
+        let cost = |node: &Option<_>| node.as_ref().map(|node: &Node<MazeLocation>| node.cost());
+
+        for node in [&consistent, &inconsistent] {
+            prop_assert_eq!(cost(node), cost(&uniform));
+
+            if let Some(node) = node {
+                let path = Vec::from(node.to_path());
+                let path_cost: f64 = path[1..].iter().map(|&loc| terrain[cell(loc)]).sum();
+
+                prop_assert!(is_path(&maze, &path));
+                prop_assert!((path_cost - node.cost().0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn dijkstra_all_finds_every_cheapest_path(maze in maze(), terrain in vec(1.0..10.0f64, 400)) {
+        let cell = |loc: MazeLocation| loc.row as usize * 20 + loc.column as usize;
+        let successors = |loc| {
+            maze.get_successors(loc)
+                .into_iter()
+                .map(|succ| (succ, OrderedFloat(terrain[cell(succ)])))
+                .collect::<Vec<_>>()
+        };
+
+        let paths = dijkstra_all(maze.start, successors);
+        let goal = dijkstra(maze.start, |loc| maze.test_goal(loc), successors);
+
+        prop_assert_eq!(paths.distances.get(&maze.goal).copied(), goal.map(|node| node.cost()));
+
+        for (&loc, &distance) in &paths.distances {
+            let path = Vec::from(paths.path_to(&loc).unwrap());
+            let path_cost: f64 = path[1..].iter().map(|&loc| terrain[cell(loc)]).sum();
+
+            prop_assert_eq!(path[0], maze.start);
+            prop_assert!(path.windows(2).all(|step| maze.get_successors(step[0]).contains(&step[1])));
+            prop_assert!((path_cost - distance.0).abs() < 1e-9);
+
+            // No move leads anywhere more cheaply than the distance found for it, so none of them is too long.
+            for (succ, step_cost) in successors(loc) {
+                prop_assert!(paths.distances[&succ] <= distance + step_cost);
+            }
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_finds_paths_as_short_as_bfs(maze in maze_up_to(6)) {
+        let bfs_path = bfs(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            |loc| maze.get_successors(loc),
+        )
+        .map(|node| node.to_path().len());
+
+        let iddfs_found = iddfs(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            |loc| maze.get_successors(loc),
+            100,
+        );
+        let ida_star_found = ida_star(
+            maze.start,
+            |loc| maze.test_goal(loc),
+            |loc| maze.get_successors(loc),
+            |loc| OrderedFloat(maze.distance_to_goal(loc).into()),
+            OrderedFloat(100.0),
+        );
+
+        for found in [iddfs_found, ida_star_found] {
+            // No path in a maze this size comes anywhere near a cost of 100, heuristic included, so the limit never gets in the way.
+            prop_assert_eq!(found.exhausted, bfs_path.is_none());
+
+            if let Some(solution) = found.solution {
+                let path = Vec::from(solution.to_path());
+
+                prop_assert!(is_path(&maze, &path));
+                prop_assert_eq!(Some(path.len()), bfs_path);
+                prop_assert_eq!(solution.cost(), OrderedFloat((path.len() - 1) as f64));
+            } else {
+                prop_assert_eq!(bfs_path, None);
+            }
+        }
+    }
+}